@@ -7,7 +7,10 @@ use zenis_discord::{
     application_command::CommandOptionValue,
     twilight_http::client::InteractionClient,
     twilight_model::{
-        application::command::CommandOptionType,
+        application::{
+            command::{CommandOptionType, CommandType},
+            interaction::InteractionType,
+        },
         gateway::payload::incoming::InteractionCreate,
         id::{
             marker::{ApplicationMarker, GuildMarker},
@@ -37,15 +40,16 @@ pub async fn execute_command(
 
     let mut options = data.options.clone();
 
+    // Context-menu commands carry no options and are keyed by their full name
     let subcommand = match data.options.first() {
-        Some(option) => match &option.value {
+        Some(option) if data.kind == CommandType::ChatInput => match &option.value {
             CommandOptionValue::SubCommand(suboptions) => {
                 options = suboptions.clone();
                 Some(option.name.clone())
             }
             _ => None,
         },
-        None => None,
+        _ => None,
     };
 
     let command_key = match subcommand {
@@ -53,7 +57,8 @@ pub async fn execute_command(
         None => data.name.to_owned(),
     };
 
-    let ctx = CommandContext::new(
+    let is_autocomplete = interaction.kind == InteractionType::ApplicationCommandAutocomplete;
+    let mut ctx = CommandContext::new(
         client.clone(),
         Box::new(interaction.0),
         watcher,
@@ -64,6 +69,19 @@ pub async fn execute_command(
         .get(&command_key)
         .ok_or(anyhow::anyhow!("Command not found"))?;
 
+    if is_autocomplete {
+        let Some((option_name, query)) = ctx.options().get_focused() else {
+            return Ok(());
+        };
+
+        // A failing provider shouldn't leave the user without a response
+        let choices = command
+            .autocomplete(ctx.clone(), &option_name, query)
+            .await
+            .unwrap_or_default();
+        return ctx.respond_autocomplete(choices).await;
+    }
+
     let result = command.run(ctx).await;
     if result.is_err() {
         eprintln!("[ERROR]\n{}", result.unwrap_err());
@@ -78,31 +96,35 @@ pub async fn register_commands(application_id: Id<ApplicationMarker>, client: Ar
         let mut commands = Vec::new();
 
         for (name, command) in COMMANDS.iter() {
+            let builder = command.build_command(application_id);
+            // Context-menu names are free text and may contain spaces, so they are never grouped
+            if builder.is_context_menu() {
+                commands.push(builder);
+                continue;
+            }
+
             let splitted_name = name
                 .split_ascii_whitespace()
                 .map(str::to_owned)
                 .collect::<Vec<_>>();
             if splitted_name.len() == 1 {
-                commands.push(command.build_command(application_id));
+                commands.push(builder);
             } else {
-                parent_commands.push((
-                    splitted_name[0].clone(),
-                    command.build_command(application_id),
-                ));
+                parent_commands.push((splitted_name[0].clone(), builder));
             }
         }
 
         for (parent_name, command) in parent_commands.into_iter() {
             let parent_command = match commands
                 .iter_mut()
-                .find(|cmd| cmd.command.name == parent_name)
+                .find(|cmd| !cmd.is_context_menu() && cmd.command.name == parent_name)
             {
                 Some(command) => command,
                 None => {
                     commands.push(CommandBuilder::new(application_id, &parent_name, "Group"));
                     commands
                         .iter_mut()
-                        .find(|cmd| cmd.command.name == parent_name)
+                        .find(|cmd| !cmd.is_context_menu() && cmd.command.name == parent_name)
                         .unwrap()
                 }
             };
@@ -2,18 +2,31 @@ use std::sync::{atomic::Ordering, Arc};
 
 use chrono::Utc;
 use rand::{rngs::StdRng, Rng, SeedableRng};
-use zenis_common::{config, Color, Probability};
+use zenis_common::{config, Color};
 use zenis_database::{
-    guild_model::GuildFlag, instance_model::InstanceMessage, user_model::UserFlags, ZenisDatabase,
+    guild_model::{FloodGuardSettings, GuildFlag, GuildSettings},
+    instance_model::InstanceMessage,
+    user_model::UserFlags,
+    ZenisDatabase,
 };
 use zenis_discord::{
     twilight_gateway::Event,
-    twilight_model::gateway::payload::incoming::{
-        GuildCreate, InteractionCreate, MessageCreate, Ready,
+    twilight_model::{
+        channel::message::AllowedMentions,
+        gateway::payload::incoming::{
+            ChannelDelete, GuildCreate, GuildDelete, InteractionCreate, MessageCreate,
+            MessageUpdate, Ready, WebhooksUpdate,
+        },
+        id::{
+            marker::{ChannelMarker, MessageMarker},
+            Id,
+        },
     },
     EmbedAuthor, EmbedBuilder, UserExtension,
 };
-use zenis_framework::{watcher::Watcher, ZenisClient};
+use zenis_framework::{
+    util::is_not_found_error, watcher::Watcher, FloodTrigger, FloodVerdict, ZenisClient,
+};
 
 use crate::command_handler;
 
@@ -57,9 +70,31 @@ impl EventHandler {
             Event::MessageCreate(message) => {
                 self.message_create(message).await.ok();
             }
+            Event::MessageUpdate(message) => {
+                self.message_update(message).await.ok();
+            }
+            Event::MessageDelete(message) => {
+                self.messages_delete(message.channel_id, &[message.id])
+                    .await
+                    .ok();
+            }
+            Event::MessageDeleteBulk(messages) => {
+                self.messages_delete(messages.channel_id, &messages.ids)
+                    .await
+                    .ok();
+            }
             Event::GuildCreate(guild_create) => {
                 self.guild_create(guild_create).await.ok();
             }
+            Event::GuildDelete(guild_delete) => {
+                self.guild_delete(guild_delete).await.ok();
+            }
+            Event::ChannelDelete(channel_delete) => {
+                self.channel_delete(channel_delete).await.ok();
+            }
+            Event::WebhooksUpdate(webhooks_update) => {
+                self.webhooks_update(webhooks_update).await.ok();
+            }
             _ => {}
         };
     }
@@ -90,10 +125,7 @@ impl EventHandler {
             .model()
             .await?;
 
-        if config::BOT_IDS.contains(&message.author.id.get())
-            || message.content.starts_with('>')
-            || message.content.starts_with('_')
-        {
+        if config::BOT_IDS.contains(&message.author.id.get()) {
             return Ok(());
         }
 
@@ -116,6 +148,50 @@ impl EventHandler {
             .get_all_by_channel(channel.id.get())
             .await?;
 
+        if instances.is_empty() {
+            return Ok(());
+        }
+
+        let (guild_settings, flood_guard_settings) = match message.guild_id {
+            Some(guild_id) => {
+                let guild_data = self.database.guilds().get_by_guild(guild_id).await?;
+                (guild_data.settings, guild_data.flood_guard)
+            }
+            None => (GuildSettings::default(), FloodGuardSettings::default()),
+        };
+
+        if guild_settings.is_ignored_message(&message.content) {
+            return Ok(());
+        }
+
+        // Agents (webhooks) talking to each other already have their own stop chance
+        let merge_into_last_block = if message.webhook_id.is_none() {
+            let check = self.client.flood_guard.check(
+                channel.id.get(),
+                author.id.get(),
+                &flood_guard_settings,
+                Utc::now().timestamp_millis(),
+            );
+
+            if let Some(trigger) = check.trigger {
+                self.send_flood_notice(channel.id, trigger).await.ok();
+            }
+
+            match check.verdict {
+                FloodVerdict::Drop => return Ok(()),
+                FloodVerdict::Merge => true,
+                FloodVerdict::Accept => false,
+            }
+        } else {
+            false
+        };
+
+        // Replying to an agent message counts as addressing that agent
+        let replied_message_id = message
+            .referenced_message
+            .as_ref()
+            .map(|referenced| referenced.id.get());
+
         let len = instances.len() as i64;
         for instance in instances.iter_mut() {
             if instance.agent_name == author.display_name() && author.bot {
@@ -130,31 +206,48 @@ impl EventHandler {
                 message.author.display_name(),
                 message.author.name,
                 message.author.id,
-                Utc::now().format("%d-%m-%Y %H:%M:%S"),
+                guild_settings.format_date(Utc::now()),
                 message.id,
                 channel.name.clone().unwrap_or(String::from("n-a")),
                 channel.id,
                 content
             );
 
-            instance.push_message(InstanceMessage {
-                is_assistant: false,
-                text: formated_content,
-                user_id: message.author.id.get(),
-                image_url: None,
-            });
+            let merged = merge_into_last_block
+                && instance.append_to_last_block(
+                    message.author.id.get(),
+                    message.id.get(),
+                    &content,
+                );
+            if !merged {
+                instance.push_message(InstanceMessage {
+                    is_assistant: false,
+                    text: formated_content,
+                    user_id: message.author.id.get(),
+                    image_url: None,
+                    message_id: None,
+                });
+            }
+
+            // Messages the agent wouldn't answer are kept as context, but don't trigger a (paid) LLM call.
+            // Rapid-fire messages only trigger one when they address the agent
+            let is_addressed = instance.is_addressed_by(&message.content, replied_message_id);
+            if (!merged || is_addressed)
+                && instance.wants_to_reply(
+                    is_addressed,
+                    author.bot,
+                    StdRng::from_os_rng().random_range(0..100),
+                )
+            {
+                instance.is_awaiting_new_messages = false;
+            }
 
-            instance.is_awaiting_new_messages = false;
             instance.last_received_message_timestamp +=
                 StdRng::from_os_rng().random_range(1..=3) + len;
 
             if author.bot {
                 instance.last_sent_message_timestamp +=
                     StdRng::from_os_rng().random_range(2..=4) + len;
-
-                if Probability::new(30).generate_random_bool() {
-                    instance.is_awaiting_new_messages = true;
-                }
             }
 
             self.database.instances().save(instance.clone()).await?;
@@ -163,6 +256,153 @@ impl EventHandler {
         Ok(())
     }
 
+    async fn send_flood_notice(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        trigger: FloodTrigger,
+    ) -> anyhow::Result<()> {
+        let description = match trigger {
+            FloodTrigger::UserLimited(user_id) => format!(
+                "<@{user_id}> está enviando mensagens rápido demais. Os agentes deste chat vão ignorar as mensagens dele até o ritmo diminuir."
+            ),
+            FloodTrigger::UserIgnored { user_id, minutes } => format!(
+                "<@{user_id}> enviou mensagens rápido demais e será ignorado pelos agentes deste chat por **{minutes} minutos**."
+            ),
+            FloodTrigger::ChannelLimited => "Este chat está recebendo mensagens rápido demais. Os agentes vão ignorar as mensagens novas até o ritmo diminuir.".to_string(),
+        };
+
+        let embed = EmbedBuilder::new_common()
+            .set_color(Color::YELLOW)
+            .set_author(EmbedAuthor {
+                name: "Proteção contra flood".to_string(),
+                icon_url: None,
+            })
+            .set_description(description)
+            .add_footer_text("Administradores podem ajustar os limites em /servidor.");
+
+        self.client
+            .http
+            .create_message(channel_id)
+            .embeds(&[embed.build()])
+            .allowed_mentions(Some(&AllowedMentions::default()))
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn message_update(self, message: Box<MessageUpdate>) -> anyhow::Result<()> {
+        if message.author.bot {
+            return Ok(());
+        }
+
+        let content = message.content.chars().take(1000).collect::<String>();
+        let instances = self
+            .database
+            .instances()
+            .get_all_by_channel(message.channel_id.get())
+            .await?;
+
+        for mut instance in instances {
+            if instance.edit_user_message(message.id.get(), &content) {
+                self.database.instances().save(instance).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn messages_delete(
+        self,
+        channel_id: Id<ChannelMarker>,
+        message_ids: &[Id<MessageMarker>],
+    ) -> anyhow::Result<()> {
+        let message_ids = message_ids.iter().map(|id| id.get()).collect::<Vec<_>>();
+        let instances = self
+            .database
+            .instances()
+            .get_all_by_channel(channel_id.get())
+            .await?;
+
+        for mut instance in instances {
+            if instance.remove_messages(&message_ids) {
+                self.database.instances().save(instance).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn channel_delete(self, channel_delete: Box<ChannelDelete>) -> anyhow::Result<()> {
+        if let Some(webhook) = self
+            .database
+            .channel_webhooks()
+            .get_by_channel(channel_delete.0.id.get())
+            .await?
+        {
+            self.database
+                .channel_webhooks()
+                .delete_webhook(&webhook)
+                .await?;
+        }
+
+        let instances = self
+            .database
+            .instances()
+            .all_actives_in_channel(channel_delete.0.id.get())
+            .await?;
+
+        for mut instance in instances {
+            instance.exit_reason = Some("O canal foi apagado".to_string());
+            self.database.instances().save(instance).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn guild_delete(self, guild_delete: GuildDelete) -> anyhow::Result<()> {
+        // An unavailable guild is just an outage, the bot is still there
+        if guild_delete.unavailable == Some(true) {
+            return Ok(());
+        }
+
+        let instances = self
+            .database
+            .instances()
+            .all_actives_in_guild(guild_delete.id.get())
+            .await?;
+
+        for mut instance in instances {
+            instance.exit_reason = Some("Fui removido do servidor".to_string());
+            self.database.instances().save(instance).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn webhooks_update(self, webhooks_update: WebhooksUpdate) -> anyhow::Result<()> {
+        let instances = self
+            .database
+            .instances()
+            .all_actives_in_channel(webhooks_update.channel_id.get())
+            .await?;
+
+        for mut instance in instances {
+            match self.client.http.webhook(Id::new(instance.webhook_id)).await {
+                Err(e) if is_not_found_error(&e) => {}
+                _ => continue,
+            }
+
+            // The agent keeps going with a new webhook, unless it can't be created
+            self.client
+                .recreate_instance_webhook(&self.database, &mut instance)
+                .await
+                .ok();
+            self.database.instances().save(instance).await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn guild_create(self, guild_create: Box<GuildCreate>) -> anyhow::Result<()> {
         let guild_create = match *guild_create {
             GuildCreate::Available(guild) => guild,
@@ -1,7 +1,13 @@
 mod command_handler;
 mod event_handler;
 
-use std::{collections::HashMap, net::SocketAddr, str::FromStr, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
 use chrono::Utc;
 pub use event_handler::EventHandler;
@@ -17,17 +23,26 @@ use zenis_common::{config, Color};
 use zenis_data::products::PRODUCTS;
 use zenis_database::{
     bson::oid::ObjectId,
-    instance_model::{CreditsPaymentMethod, InstanceModel},
+    earning_model::EarningSource,
+    guild_model::GuildSettings,
+    instance_model::{CreditsPaymentMethod, InstanceModel, SpendingCapPeriod},
+    outbox_model::OutboxMessageModel,
     transaction::CreditDestination,
     DatabaseState, ZenisDatabase,
 };
 
 use zenis_discord::{
+    render_message,
     twilight_gateway::{EventTypeFlags, Intents, Shard, ShardId, StreamExt},
+    twilight_http,
     twilight_model::id::Id,
-    DiscordHttpClient, EmbedBuilder,
+    EmbedBuilder, OutputSafetyPolicy, RenderedMessage,
+};
+use zenis_framework::{
+    util::{agent_render_options, is_not_found_error, ratelimit_retry_after},
+    watcher::Watcher,
+    ZenisClient,
 };
-use zenis_framework::{watcher::Watcher, ZenisClient};
 use zenis_payment::mp::{client::MercadoPagoClient, notification::NotificationPayload};
 
 use warp::http::Response as WarpResponse;
@@ -46,6 +61,7 @@ async fn main() {
     let intents = Intents::GUILD_MESSAGES
         | Intents::MESSAGE_CONTENT
         | Intents::GUILD_MEMBERS
+        | Intents::GUILD_WEBHOOKS
         | Intents::GUILDS;
 
     let mp_client = MercadoPagoClient::new(
@@ -163,7 +179,6 @@ async fn main() {
             loop {
                 tokio::time::sleep(Duration::from_secs(3)).await;
 
-                let http = client.http.clone();
                 let instances = db.instances().all_actives().await.unwrap_or_default();
 
                 // Map instances to channel_ids
@@ -185,24 +200,34 @@ async fn main() {
                         continue;
                     };
 
-                    let result = process_instance(
-                        http.clone(),
-                        client.clone(),
-                        db.clone(),
-                        instance.clone(),
-                    )
-                    .await;
+                    let result =
+                        process_instance(client.clone(), db.clone(), instance.clone()).await;
 
+                    // Reloaded, so changes saved while processing aren't overwritten
                     if result.is_err() {
-                        instance.increment_error();
-                        db.instances().save(instance.clone()).await.ok();
+                        if let Ok(Some(mut instance)) = db.instances().get_by_id(instance.id).await
+                        {
+                            instance.increment_error();
+                            db.instances().save(instance).await.ok();
+                        }
                     }
 
                     let last_message_timestamp = instance.last_received_message_timestamp;
                     let now = chrono::Utc::now();
 
-                    const MINUTES_TOLERANCE: i64 = 8;
-                    if (now.timestamp() - last_message_timestamp) > 60 * MINUTES_TOLERANCE {
+                    let guild_settings = match instance.guild_id {
+                        Some(guild_id) => db
+                            .guilds()
+                            .get_by_guild(Id::new(guild_id))
+                            .await
+                            .map(|guild_data| guild_data.settings)
+                            .unwrap_or_default(),
+                        None => GuildSettings::default(),
+                    };
+
+                    if (now.timestamp() - last_message_timestamp)
+                        > 60 * guild_settings.inactivity_minutes
+                    {
                         let Ok(Some(mut instance)) = db.instances().get_by_id(instance.id).await
                         else {
                             continue;
@@ -213,8 +238,10 @@ async fn main() {
                     }
                 }
 
+                process_outbox(client.clone(), db.clone()).await.ok();
                 client.delete_off_instances(db.clone()).await.ok();
                 db.transactions().delete_expired_transactions().await.ok();
+                db.archived_instances().delete_expired_archives().await.ok();
             }
         });
     }
@@ -234,7 +261,6 @@ async fn main() {
 }
 
 async fn process_instance(
-    http: Arc<DiscordHttpClient>,
     client: Arc<ZenisClient>,
     database: Arc<ZenisDatabase>,
     mut instance: InstanceModel,
@@ -243,8 +269,9 @@ async fn process_instance(
         return Ok(());
     }
 
-    let diff = Utc::now().timestamp() - instance.last_sent_message_timestamp;
-    if diff < 7 {
+    let now = Utc::now().timestamp();
+    let diff = now - instance.last_sent_message_timestamp;
+    if diff < instance.settings.min_delay_secs || instance.is_rate_limited(now) {
         return Ok(());
     }
 
@@ -326,19 +353,154 @@ async fn process_instance(
         return Ok(());
     };
 
-    let (webhook_id, token) = (instance.webhook_id, instance.webhook_token.clone());
+    instance.register_reply(Utc::now().timestamp());
+    database.instances().save(instance.clone()).await?;
 
-    http.execute_webhook(Id::new(webhook_id), &token)
-        .content(&message)
-        .await
-        .ok();
+    database
+        .outbox()
+        .create_message(OutboxMessageModel::new(
+            &instance,
+            &response_content,
+            &message,
+            image_processed,
+        ))
+        .await?;
+
+    Ok(())
+}
+
+/// Delivers the queued agent replies. Each one is only charged after Discord confirms it was sent
+async fn process_outbox(
+    client: Arc<ZenisClient>,
+    database: Arc<ZenisDatabase>,
+) -> anyhow::Result<()> {
+    let now = Utc::now().timestamp();
+    // A channel waiting for a retry holds its newer replies, so they never arrive out of order
+    let mut held_channel_ids = HashSet::new();
+
+    for mut outbox_message in database.outbox().all_pending().await? {
+        if held_channel_ids.contains(&outbox_message.channel_id) {
+            continue;
+        }
+
+        if outbox_message.next_attempt_timestamp > now {
+            held_channel_ids.insert(outbox_message.channel_id);
+            continue;
+        }
+
+        let Some(mut instance) = database
+            .instances()
+            .get_by_id(outbox_message.instance_id)
+            .await?
+        else {
+            database.outbox().delete_message(outbox_message.id).await?;
+            continue;
+        };
+
+        let policy = client.output_safety_policy(&database, &instance).await?;
+        let (content, _) = policy.sanitize(&outbox_message.content);
+        let parts = render_message(&content, agent_render_options(&instance));
+        if parts.is_empty() {
+            database.outbox().delete_message(outbox_message.id).await?;
+            continue;
+        }
+
+        // Parts are sent one at a time, so a retry doesn't repeat the ones already delivered
+        let mut result = Ok(());
+        let mut last_message_id = None;
+        for part in parts.iter().skip(outbox_message.delivered_parts as usize) {
+            match deliver_agent_message(&client, &database, &mut instance, part, &policy).await {
+                Ok(message_id) => {
+                    outbox_message.delivered_parts += 1;
+                    last_message_id = message_id.or(last_message_id);
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
 
-    process_instance_credits_payment(&mut instance, database.clone(), image_processed).await?;
-    database.instances().save(instance).await?;
+        match result {
+            Ok(()) => {
+                database.outbox().delete_message(outbox_message.id).await?;
+
+                if let Some(message_id) = last_message_id {
+                    instance.set_reply_message_id(&outbox_message.history_text, message_id);
+                }
+
+                process_instance_credits_payment(
+                    &mut instance,
+                    database.clone(),
+                    outbox_message.image_processed,
+                )
+                .await?;
+                check_spending_cap(&client, &mut instance).await;
+                database.instances().save(instance).await?;
+            }
+            Err(e) => {
+                outbox_message.attempts += 1;
+
+                if outbox_message.attempts >= config::OUTBOX_MAX_ATTEMPTS {
+                    database.outbox().delete_message(outbox_message.id).await?;
+                    client
+                        .emit_error_hook(
+                            format!(
+                                "Agent reply not delivered after {} attempts, so it wasn't charged. Agent ID: {}",
+                                outbox_message.attempts, outbox_message.agent_identifier
+                            ),
+                            e,
+                        )
+                        .await
+                        .ok();
+                    continue;
+                }
+
+                let retry_after = e
+                    .downcast_ref::<twilight_http::Error>()
+                    .and_then(ratelimit_retry_after)
+                    .map(|secs| secs.ceil() as i64)
+                    .unwrap_or(2_i64.pow(outbox_message.attempts));
+
+                held_channel_ids.insert(outbox_message.channel_id);
+                outbox_message.last_error = Some(e.to_string());
+                outbox_message.next_attempt_timestamp = now + retry_after;
+                database.outbox().save(outbox_message).await?;
+            }
+        }
+    }
 
     Ok(())
 }
 
+/// Sends a reply as the agent, giving it a new webhook if someone deleted the old one. Returns the sent message ID
+async fn deliver_agent_message(
+    client: &ZenisClient,
+    database: &ZenisDatabase,
+    instance: &mut InstanceModel,
+    message: &RenderedMessage,
+    policy: &OutputSafetyPolicy,
+) -> anyhow::Result<Option<u64>> {
+    let response = match client
+        .execute_agent_webhook(instance, message, policy)
+        .await
+    {
+        Err(e) if is_not_found_error(&e) => {
+            let recreated = client.recreate_instance_webhook(database, instance).await;
+            database.instances().save(instance.clone()).await?;
+            recreated?;
+
+            client
+                .execute_agent_webhook(instance, message, policy)
+                .await?
+        }
+        result => result?,
+    };
+
+    // The message was already sent, so a body that fails to parse isn't a failed delivery
+    Ok(response.model().await.ok().map(|message| message.id.get()))
+}
+
 async fn process_instance_credits_payment(
     instance: &mut InstanceModel,
     database: Arc<ZenisDatabase>,
@@ -346,6 +508,7 @@ async fn process_instance_credits_payment(
 ) -> anyhow::Result<()> {
     let payment_method = instance.payment_method;
     let price_per_reply = instance.pricing.price_per_reply + if image_processed { 5 } else { 0 };
+    instance.register_spending(price_per_reply, Utc::now().timestamp());
 
     match payment_method {
         CreditsPaymentMethod::UserCredits(user_id) => {
@@ -376,9 +539,78 @@ async fn process_instance_credits_payment(
         }
     }
 
+    // The creator's share is over the agent's own price, not the brain or image extras
+    if let Some(agent) = database
+        .agents()
+        .get_by_identifier(&instance.agent_identifier)
+        .await?
+    {
+        let agent_price_per_reply =
+            instance.pricing.price_per_reply - instance.brain.extra_price_per_reply();
+        database
+            .earnings()
+            .credit_creators(
+                &agent,
+                EarningSource::Reply,
+                agent_price_per_reply,
+                instance.payment_method,
+            )
+            .await?;
+    }
+
     Ok(())
 }
 
+/// Shuts the instance down before a reply would go over its spending cap, warning the channel when it gets close
+async fn check_spending_cap(client: &ZenisClient, instance: &mut InstanceModel) {
+    let now = Utc::now().timestamp();
+    let Some((spent, cap)) = instance.capped_spending(now) else {
+        return;
+    };
+
+    if instance.exit_reason.is_some() {
+        return;
+    }
+
+    let period = match cap.period {
+        SpendingCapPeriod::Session => "da sessão",
+        SpendingCapPeriod::Hour => "por hora",
+    };
+
+    if instance.would_exceed_spending_cap(instance.pricing.price_per_reply, now) {
+        instance.exit_reason = Some(format!(
+            "O limite de gastos {period} definido na invocação foi atingido ({spent}/{}₢)",
+            cap.max_credits
+        ));
+        return;
+    }
+
+    // Hourly usage goes down again, so the warning can come back later
+    if spent * 100 < cap.max_credits * 80 {
+        instance.spending_warning_sent = false;
+        return;
+    }
+
+    if instance.spending_warning_sent {
+        return;
+    }
+
+    instance.spending_warning_sent = true;
+    let embed = EmbedBuilder::new_common()
+        .set_color(Color::YELLOW)
+        .set_description(format!(
+            "⚠️ **{}** já gastou **{spent}₢** de **{}₢** do limite de gastos {period}. Ao atingir o limite, o agente será desligado.",
+            instance.agent_name, cap.max_credits
+        ));
+
+    client
+        .http
+        .create_message(Id::new(instance.channel_id))
+        .embeds(&[embed.build()])
+        .await
+        .ok();
+}
+
 pub async fn process_mp_notification(
     transaction_id: String,
     payload: NotificationPayload,
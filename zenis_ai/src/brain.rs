@@ -3,6 +3,8 @@ use std::{fmt::Debug, sync::Arc};
 use async_trait::async_trait;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use zenis_common::config;
+use zenis_database::agent_model::AgentExampleDialogue;
 
 use crate::common::{ArenaCharacter, ArenaMessage, ChatMessage, ChatResponse};
 
@@ -14,7 +16,6 @@ pub struct BrainParameters {
     pub model: String,
     pub max_tokens: usize,
     pub system_prompt: String,
-    pub strip_italic_actions: bool,
 }
 
 pub const DEFAULT_CHAT_SYSTEM_PROMPT: &str = include_str!("default_chat_system_prompt.txt");
@@ -27,6 +28,28 @@ pub const ARENA_PARTIAL_EXAMPLES: &str = include_str!("arena_partial_examples.tx
 pub const ARENA_CONTEXT_GENERATION_PROMPT: &str =
     include_str!("arena_context_generation_prompt.txt");
 
+/// The creator's example exchanges of an agent, in the same IN/OUT format of the global examples,
+/// to be appended to the agent's system prompt. Empty if the agent has none
+pub fn make_agent_examples_prompt(agent_name: &str, dialogues: &[AgentExampleDialogue]) -> String {
+    if dialogues.is_empty() {
+        return String::new();
+    }
+
+    let examples = dialogues
+        .iter()
+        .take(config::MAX_EXAMPLE_DIALOGUES)
+        .map(|dialogue| {
+            format!(
+                "IN: <!name/>Usuário\n<!message/>{}\n\nOUT: <!message/>{}",
+                dialogue.user_message, dialogue.agent_message
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    format!("\n<!agent_examples/>Exemplos de conversa de {agent_name}, escritos pelo seu criador. Fale com a mesma voz e estilo desde a primeira mensagem, sem copiar as respostas:\n{examples}")
+}
+
 #[async_trait]
 pub trait Brain {
     fn api_key(&self, debug: bool) -> String;
@@ -37,7 +60,6 @@ pub trait Brain {
             model: "unknown".to_string(),
             max_tokens: 1024,
             system_prompt: String::new(),
-            strip_italic_actions: false,
         }
     }
 
@@ -0,0 +1,486 @@
+use anyhow::{bail, Context};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use zenis_database::agent_model::{AgentExampleDialogue, AgentLoreEntry, AgentModel};
+
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+/// Keyword of the `tEXt` chunk that holds the base64 card in PNG cards
+const PNG_CARD_KEYWORD: &str = "chara";
+const MAX_CARD_SIZE: usize = 8 * 1024 * 1024;
+
+/// A character in the community Character Card V2 format (`chara_card_v2`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CharacterCard {
+    pub spec: String,
+    pub spec_version: String,
+    pub data: CharacterCardData,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterCardData {
+    pub name: String,
+    pub description: String,
+    pub personality: String,
+    pub scenario: String,
+    pub first_mes: String,
+    pub mes_example: String,
+    pub creator_notes: String,
+    pub system_prompt: String,
+    pub post_history_instructions: String,
+    pub alternate_greetings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub character_book: Option<CharacterBook>,
+    pub tags: Vec<String>,
+    pub creator: String,
+    pub character_version: String,
+    pub extensions: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterBook {
+    pub entries: Vec<CharacterBookEntry>,
+    pub extensions: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterBookEntry {
+    pub keys: Vec<String>,
+    pub content: String,
+    pub enabled: bool,
+    pub insertion_order: i64,
+    pub extensions: serde_json::Value,
+}
+
+impl Default for CharacterBookEntry {
+    fn default() -> Self {
+        Self {
+            keys: vec![],
+            content: String::new(),
+            enabled: true,
+            insertion_order: 0,
+            extensions: json!({}),
+        }
+    }
+}
+
+/// The agent fields a card maps to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImportedAgent {
+    pub name: String,
+    pub description: String,
+    pub introduction_message: String,
+    pub example_dialogues: Vec<AgentExampleDialogue>,
+    pub lore: Vec<AgentLoreEntry>,
+}
+
+impl CharacterCard {
+    pub fn from_agent(agent: &AgentModel) -> Self {
+        let lore = agent
+            .lore
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| CharacterBookEntry {
+                keys: entry.keys.clone(),
+                content: entry.content.clone(),
+                insertion_order: index as i64,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            spec: "chara_card_v2".to_string(),
+            spec_version: "2.0".to_string(),
+            data: CharacterCardData {
+                name: agent.name.clone(),
+                description: agent.description.clone(),
+                first_mes: agent.introduction_message.clone(),
+                mes_example: render_example_dialogues(&agent.example_dialogues),
+                creator: agent
+                    .identifier
+                    .split('/')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                character_book: (!lore.is_empty()).then(|| CharacterBook {
+                    entries: lore,
+                    extensions: json!({}),
+                }),
+                extensions: json!({ "zenis": { "identifier": agent.identifier } }),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Reads a `.json` card (V2, or the older flat V1) or a PNG with the card in a `tEXt` chunk
+    pub fn parse(bytes: &[u8]) -> anyhow::Result<Self> {
+        let json = if bytes.starts_with(PNG_SIGNATURE) {
+            let encoded = read_png_text_chunk(bytes, PNG_CARD_KEYWORD)
+                .context("Expected a character card inside the PNG")?;
+            base64::engine::general_purpose::STANDARD.decode(encoded.trim())?
+        } else {
+            bytes.to_vec()
+        };
+
+        let value: serde_json::Value = serde_json::from_slice(&json)?;
+        let card = if value.get("data").is_some_and(|data| data.is_object()) {
+            serde_json::from_value(value)?
+        } else {
+            Self {
+                spec: "chara_card_v2".to_string(),
+                spec_version: "2.0".to_string(),
+                data: serde_json::from_value(value)?,
+            }
+        };
+
+        if card.data.name.trim().is_empty() {
+            bail!("The character card has no name");
+        }
+
+        Ok(card)
+    }
+
+    pub async fn download(url: &str) -> anyhow::Result<Self> {
+        Self::parse(&download_bytes(url).await?)
+    }
+
+    /// Maps the card onto agent fields. `{{char}}` and `{{user}}` placeholders are filled in
+    pub fn to_imported_agent(&self) -> ImportedAgent {
+        let data = &self.data;
+        let name = data.name.trim().replace('\n', " ");
+        let fill = |text: &str| fill_placeholders(text, &name);
+
+        let mut description = fill(&data.description).trim().to_owned();
+        for (label, text) in [
+            ("Personalidade", &data.personality),
+            ("Cenário", &data.scenario),
+        ] {
+            let text = fill(text);
+            if !text.trim().is_empty() {
+                description.push_str(&format!("\n\n{label}: {}", text.trim()));
+            }
+        }
+
+        let lore = data
+            .character_book
+            .iter()
+            .flat_map(|book| book.entries.iter())
+            .filter(|entry| entry.enabled && !entry.content.trim().is_empty())
+            .map(|entry| AgentLoreEntry {
+                keys: entry.keys.clone(),
+                content: fill(&entry.content).trim().to_owned(),
+            })
+            .collect();
+
+        ImportedAgent {
+            description: description.trim().to_owned(),
+            introduction_message: fill(&data.first_mes).trim().to_owned(),
+            example_dialogues: parse_example_dialogues(&data.mes_example, &name),
+            lore,
+            name,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// A PNG card with the agent image (or a blank one) carrying the card in a `tEXt` chunk
+    pub async fn to_png(&self, image_url: Option<&str>) -> anyhow::Result<Vec<u8>> {
+        let image = match image_url {
+            Some(url) => image::load_from_memory(&download_bytes(url).await?)?,
+            None => image::DynamicImage::new_rgb8(400, 600),
+        };
+
+        let mut png = std::io::Cursor::new(vec![]);
+        image.write_to(&mut png, image::ImageFormat::Png)?;
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(self.to_json());
+        insert_png_text_chunk(png.into_inner(), PNG_CARD_KEYWORD, &encoded)
+    }
+}
+
+async fn download_bytes(url: &str) -> anyhow::Result<Vec<u8>> {
+    let bytes = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+    if bytes.len() > MAX_CARD_SIZE {
+        bail!("File too large: {} bytes", bytes.len());
+    }
+
+    Ok(bytes.to_vec())
+}
+
+fn fill_placeholders(text: &str, name: &str) -> String {
+    text.replace("{{char}}", name)
+        .replace("{{Char}}", name)
+        .replace("<BOT>", name)
+        .replace("{{user}}", "usuário")
+        .replace("{{User}}", "usuário")
+        .replace("<USER>", "usuário")
+}
+
+/// Parses `mes_example`: blocks started by `<START>`, with `{{user}}:` and `{{char}}:` lines
+pub fn parse_example_dialogues(mes_example: &str, name: &str) -> Vec<AgentExampleDialogue> {
+    let mut dialogues = vec![];
+
+    for block in mes_example.split("<START>") {
+        let mut user_message: Option<String> = None;
+        let mut agent_message: Option<String> = None;
+        let mut current: Option<bool> = None;
+
+        let mut flush = |user: &mut Option<String>, agent: &mut Option<String>| {
+            if let (Some(user_message), Some(agent_message)) = (user.take(), agent.take()) {
+                dialogues.push(AgentExampleDialogue {
+                    user_message: fill_placeholders(user_message.trim(), name),
+                    agent_message: fill_placeholders(agent_message.trim(), name),
+                });
+            }
+        };
+
+        for line in block.lines() {
+            let trimmed = line.trim_start();
+            let (is_user, text) = if let Some(text) = ["{{user}}:", "<USER>:"]
+                .iter()
+                .find_map(|prefix| trimmed.strip_prefix(prefix))
+            {
+                (Some(true), text)
+            } else if let Some(text) = ["{{char}}:", "<BOT>:"]
+                .iter()
+                .find_map(|prefix| trimmed.strip_prefix(prefix))
+            {
+                (Some(false), text)
+            } else {
+                (None, line)
+            };
+
+            match (is_user, current) {
+                (Some(true), _) => {
+                    // A new user line after an answer starts a new exchange
+                    if agent_message.is_some() {
+                        flush(&mut user_message, &mut agent_message);
+                    }
+
+                    let message = user_message.get_or_insert_with(String::new);
+                    message.push_str(&format!("\n{}", text.trim()));
+                    current = Some(true);
+                }
+                (Some(false), _) => {
+                    let message = agent_message.get_or_insert_with(String::new);
+                    message.push_str(&format!("\n{}", text.trim()));
+                    current = Some(false);
+                }
+                (None, Some(true)) => {
+                    if let Some(message) = user_message.as_mut() {
+                        message.push_str(&format!("\n{text}"));
+                    }
+                }
+                (None, Some(false)) => {
+                    if let Some(message) = agent_message.as_mut() {
+                        message.push_str(&format!("\n{text}"));
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
+        flush(&mut user_message, &mut agent_message);
+    }
+
+    dialogues
+}
+
+pub fn render_example_dialogues(dialogues: &[AgentExampleDialogue]) -> String {
+    dialogues
+        .iter()
+        .map(|dialogue| {
+            format!(
+                "<START>\n{{{{user}}}}: {}\n{{{{char}}}}: {}",
+                dialogue.user_message, dialogue.agent_message
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Every chunk of a PNG as (type, data)
+fn png_chunks(bytes: &[u8]) -> anyhow::Result<Vec<([u8; 4], &[u8])>> {
+    let mut chunks = vec![];
+    let mut position = PNG_SIGNATURE.len();
+
+    while position + 8 <= bytes.len() {
+        let length = u32::from_be_bytes(bytes[position..position + 4].try_into()?) as usize;
+        let kind: [u8; 4] = bytes[position + 4..position + 8].try_into()?;
+        let data_end = (position + 8)
+            .checked_add(length)
+            .filter(|data_end| data_end + 4 <= bytes.len())
+            .context("Truncated PNG chunk")?;
+        let data = &bytes[position + 8..data_end];
+
+        let mut crc = crc32fast::Hasher::new();
+        crc.update(&kind);
+        crc.update(data);
+        if crc.finalize().to_be_bytes() != bytes[data_end..data_end + 4] {
+            bail!("PNG chunk with a wrong CRC");
+        }
+
+        chunks.push((kind, data));
+        position = data_end + 4;
+
+        if &kind == b"IEND" {
+            break;
+        }
+    }
+
+    Ok(chunks)
+}
+
+fn read_png_text_chunk(bytes: &[u8], keyword: &str) -> Option<String> {
+    png_chunks(bytes)
+        .ok()?
+        .into_iter()
+        .filter(|(kind, _)| kind == b"tEXt")
+        .find_map(|(_, data)| {
+            let separator = data.iter().position(|byte| *byte == 0)?;
+            (&data[..separator] == keyword.as_bytes())
+                .then(|| String::from_utf8_lossy(&data[separator + 1..]).into_owned())
+        })
+}
+
+fn insert_png_text_chunk(png: Vec<u8>, keyword: &str, text: &str) -> anyhow::Result<Vec<u8>> {
+    let mut data = keyword.as_bytes().to_vec();
+    data.push(0);
+    data.extend_from_slice(text.as_bytes());
+
+    let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(b"tEXt");
+    crc.update(&data);
+    chunk.extend_from_slice(b"tEXt");
+    chunk.extend_from_slice(&data);
+    chunk.extend_from_slice(&crc.finalize().to_be_bytes());
+
+    // IEND is always the last chunk: 4 bytes of length, 4 of type and 4 of CRC
+    if png.len() < PNG_SIGNATURE.len() + 12 || &png[png.len() - 8..png.len() - 4] != b"IEND" {
+        bail!("Expected a PNG ending with IEND");
+    }
+
+    let iend_start = png.len() - 12;
+    let mut output = png[..iend_start].to_vec();
+    output.extend_from_slice(&chunk);
+    output.extend_from_slice(&png[iend_start..]);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARD_JSON: &str =
+        r#"{"name": "Monki", "description": "Um macaco robô", "first_mes": "Oi, {{user}}!"}"#;
+
+    fn chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut crc = crc32fast::Hasher::new();
+        crc.update(kind);
+        crc.update(data);
+
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(data);
+        chunk.extend_from_slice(&crc.finalize().to_be_bytes());
+        chunk
+    }
+
+    fn png(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend(chunk(b"IHDR", &[0; 13]));
+        for chunk in chunks {
+            png.extend_from_slice(chunk);
+        }
+        png.extend(chunk(b"IEND", &[]));
+        png
+    }
+
+    fn card_chunk(json: &str) -> Vec<u8> {
+        let mut data = b"chara\0".to_vec();
+        data.extend(
+            base64::engine::general_purpose::STANDARD
+                .encode(json)
+                .into_bytes(),
+        );
+        chunk(b"tEXt", &data)
+    }
+
+    #[test]
+    fn v1_and_v2_cards_are_parsed() {
+        let v1 = CharacterCard::parse(CARD_JSON.as_bytes()).unwrap();
+        assert_eq!(v1.data.name, "Monki");
+        assert_eq!(v1.spec, "chara_card_v2");
+
+        let v2_json =
+            format!(r#"{{"spec": "chara_card_v2", "spec_version": "2.0", "data": {CARD_JSON}}}"#);
+        let v2 = CharacterCard::parse(v2_json.as_bytes()).unwrap();
+        assert_eq!(v2, v1);
+        assert_eq!(v2.to_imported_agent().introduction_message, "Oi, usuário!");
+
+        let from_png = CharacterCard::parse(&png(&[card_chunk(&v2_json)])).unwrap();
+        assert_eq!(from_png, v1);
+    }
+
+    #[test]
+    fn written_png_cards_are_read_back() {
+        let card = CharacterCard::parse(CARD_JSON.as_bytes()).unwrap();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(card.to_json());
+        let png = insert_png_text_chunk(png(&[]), PNG_CARD_KEYWORD, &encoded).unwrap();
+
+        assert_eq!(CharacterCard::parse(&png).unwrap(), card);
+    }
+
+    #[test]
+    fn bad_signature_is_read_as_json() {
+        let mut bytes = png(&[card_chunk(CARD_JSON)]);
+        bytes[1] = b'X';
+        assert!(CharacterCard::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn truncated_chunk_is_rejected() {
+        let mut bytes = png(&[card_chunk(CARD_JSON)]);
+        // Cuts the tEXt chunk in the middle of its data, along with IEND
+        bytes.truncate(PNG_SIGNATURE.len() + 25 + 20);
+
+        assert!(png_chunks(&bytes).is_err());
+        assert!(CharacterCard::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn wrong_crc_is_rejected() {
+        let mut bytes = png(&[card_chunk(CARD_JSON)]);
+        let text_crc = bytes.len() - 12 - 1;
+        bytes[text_crc] ^= 0xFF;
+
+        assert!(png_chunks(&bytes).is_err());
+        assert!(CharacterCard::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn missing_chara_chunk_is_rejected() {
+        let bytes = png(&[chunk(b"tEXt", b"Comment\0sem card")]);
+
+        assert!(png_chunks(&bytes).is_ok());
+        assert!(read_png_text_chunk(&bytes, PNG_CARD_KEYWORD).is_none());
+        assert!(CharacterCard::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn chunk_longer_than_the_file_is_rejected() {
+        let mut bytes = png(&[card_chunk(CARD_JSON)]);
+        // Length of the tEXt chunk, right after the signature and IHDR
+        let length_start = PNG_SIGNATURE.len() + 25;
+        bytes[length_start..length_start + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        assert!(png_chunks(&bytes).is_err());
+        assert!(CharacterCard::parse(&bytes).is_err());
+    }
+}
@@ -5,7 +5,6 @@ use zenis_common::load_image_from_url;
 use crate::{
     brain::{Brain, BrainParameters, ARENA_CONTEXT_GENERATION_PROMPT},
     common::{ArenaCharacter, ArenaMessage, ArenaOutput, ChatMessage, ChatResponse, Role},
-    util::remove_italic_actions,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -60,7 +59,6 @@ impl Brain for ClaudeBrain {
             model: "claude-3-haiku-20240307".to_string(),
             max_tokens: 300,
             system_prompt: String::new(),
-            strip_italic_actions: false,
         }
     }
 
@@ -144,13 +142,7 @@ impl Brain for ClaudeBrain {
             return Err(anyhow::anyhow!("Status code: {}\n{:?}", status, text));
         }
 
-        let mut response: ClaudeChatResponse = response.json().await?;
-
-        if params.strip_italic_actions {
-            response.content.iter_mut().for_each(|reply| {
-                reply.text = reply.text.as_ref().map(|t| remove_italic_actions(t));
-            });
-        }
+        let response: ClaudeChatResponse = response.json().await?;
 
         Ok(ChatResponse {
             message: ChatMessage {
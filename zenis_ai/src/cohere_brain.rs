@@ -4,7 +4,6 @@ use serde::{Deserialize, Serialize};
 use crate::{
     brain::{Brain, BrainParameters, ARENA_CONTEXT_GENERATION_PROMPT},
     common::{ArenaCharacter, ArenaMessage, ArenaOutput, ChatMessage, ChatResponse, Role},
-    util::remove_italic_actions,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -49,7 +48,6 @@ impl Brain for CohereBrain {
             model: "command-r".to_string(),
             max_tokens: 300,
             system_prompt: String::new(),
-            strip_italic_actions: true,
         }
     }
 
@@ -108,10 +106,6 @@ impl Brain for CohereBrain {
 
         let mut response: CohereChatResponse = response.json().await?;
 
-        if params.strip_italic_actions {
-            response.text = remove_italic_actions(&response.text);
-        }
-
         let content = response.text.to_uppercase().trim().to_owned();
         if content.contains("{AWAIT}") {
             response.text = "{AWAIT}".to_string();
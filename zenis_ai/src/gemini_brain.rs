@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use zenis_common::load_image_from_url;
 
-use crate::{brain::*, common::*, util::remove_italic_actions};
+use crate::{brain::*, common::*};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct GeminiBrain {
@@ -102,7 +102,6 @@ impl Brain for GeminiBrain {
             },
             max_tokens: 12000,
             system_prompt: String::new(),
-            strip_italic_actions: false,
         }
     }
 
@@ -207,16 +206,10 @@ impl Brain for GeminiBrain {
                 })
             })
             .unwrap_or_default();
-        let final_content = if params.strip_italic_actions {
-            remove_italic_actions(&text)
-        } else {
-            text
-        };
-
         Ok(ChatResponse {
             message: ChatMessage {
                 role: Role::Assistant,
-                content: final_content.trim().to_owned(),
+                content: text.trim().to_owned(),
                 image_url: None,
             },
         })
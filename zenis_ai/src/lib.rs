@@ -1,4 +1,5 @@
 pub mod brain;
+pub mod character_card;
 pub mod claude_brain;
 pub mod cohere_brain;
 pub mod common;
@@ -7,4 +8,5 @@ pub mod common;
 pub mod gemini_brain;
 pub mod openai_brain;
 pub mod template;
+pub mod transcript;
 pub mod util;
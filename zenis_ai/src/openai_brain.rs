@@ -69,7 +69,6 @@ impl Brain for OpenAIBrain {
             },
             max_tokens: 1500,
             system_prompt: String::new(),
-            strip_italic_actions: true,
         }
     }
 
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-fn parse_string_to_hashmap(input: &str) -> HashMap<String, String> {
+pub(crate) fn parse_string_to_hashmap(input: &str) -> HashMap<String, String> {
     let mut result = HashMap::new();
     let mut key = String::new();
     let mut value = String::new();
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use zenis_database::instance_model::InstanceMessage;
+
+use crate::template::{parse_string_to_hashmap, to_assistant_object};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptRole {
+    User,
+    Agent,
+    System,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub role: TranscriptRole,
+    pub speaker: String,
+    pub date: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Transcript {
+    pub agent_name: String,
+    pub exported_at: String,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TranscriptFormat {
+    Json,
+    Markdown,
+    Html,
+}
+
+impl TranscriptFormat {
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+impl Transcript {
+    /// Turns the raw `<!key/>value` history of an instance into readable speaker lines
+    pub fn from_history(agent_name: impl ToString, history: &[InstanceMessage]) -> Self {
+        let agent_name = agent_name.to_string();
+        let mut entries = vec![];
+
+        for message in history {
+            if message.is_assistant {
+                let assistant_object = to_assistant_object(&message.text);
+                let Some(text) = assistant_object.message else {
+                    continue;
+                };
+
+                if assistant_object.is_noreply || text.trim().is_empty() {
+                    continue;
+                }
+
+                entries.push(TranscriptEntry {
+                    role: TranscriptRole::Agent,
+                    speaker: agent_name.clone(),
+                    date: None,
+                    text: text.trim().to_owned(),
+                });
+                continue;
+            }
+
+            if message.text.starts_with("<!agent_exit/>") {
+                let fields = parse_string_to_hashmap(&message.text);
+                entries.push(TranscriptEntry {
+                    role: TranscriptRole::System,
+                    speaker: String::from("Sistema"),
+                    date: None,
+                    text: format!(
+                        "{} saiu da conversa: {}",
+                        fields.get("agent_exit").cloned().unwrap_or_default(),
+                        fields.get("reason").cloned().unwrap_or_default()
+                    ),
+                });
+                continue;
+            }
+
+            // Consecutive user messages are merged into a single history entry, one `<!name/>` block each
+            for block in message.text.split("<!name/>").skip(1) {
+                let (header, text) = block.split_once("<!message/>").unwrap_or((block, ""));
+                let text = text
+                    .split_once("<!system_instruction/>")
+                    .map(|(text, _)| text)
+                    .unwrap_or(text);
+
+                let fields = parse_string_to_hashmap(&format!("<!name/>{header}"));
+                entries.push(TranscriptEntry {
+                    role: TranscriptRole::User,
+                    speaker: fields.get("name").cloned().unwrap_or_default(),
+                    date: fields.get("date").cloned(),
+                    text: text.trim().to_owned(),
+                });
+            }
+        }
+
+        Self {
+            agent_name,
+            exported_at: chrono::Utc::now().format("%d-%m-%Y %H:%M:%S").to_string(),
+            entries,
+        }
+    }
+
+    pub fn render(&self, format: TranscriptFormat) -> String {
+        match format {
+            TranscriptFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            TranscriptFormat::Markdown => self.render_markdown(),
+            TranscriptFormat::Html => self.render_html(),
+        }
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut output = format!(
+            "# Conversa com {}\n\n_Exportada em {} (UTC)_\n\n",
+            self.agent_name, self.exported_at
+        );
+
+        for entry in self.entries.iter() {
+            match entry.role {
+                TranscriptRole::System => output.push_str(&format!("> _{}_\n\n", entry.text)),
+                _ => {
+                    let date = entry
+                        .date
+                        .as_ref()
+                        .map(|date| format!(" `{date}`"))
+                        .unwrap_or_default();
+                    output.push_str(&format!("**{}**{date}\n{}\n\n", entry.speaker, entry.text));
+                }
+            }
+        }
+
+        output
+    }
+
+    fn render_html(&self) -> String {
+        let mut body = String::new();
+        for entry in self.entries.iter() {
+            let class = match entry.role {
+                TranscriptRole::User => "user",
+                TranscriptRole::Agent => "agent",
+                TranscriptRole::System => "system",
+            };
+
+            let date = entry
+                .date
+                .as_ref()
+                .map(|date| format!("<span class=\"date\">{}</span>", escape_html(date)))
+                .unwrap_or_default();
+
+            body.push_str(&format!(
+                "<div class=\"entry {class}\"><div class=\"speaker\">{}{date}</div><div class=\"text\">{}</div></div>\n",
+                escape_html(&entry.speaker),
+                escape_html(&entry.text).replace('\n', "<br>")
+            ));
+        }
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="pt-BR">
+<head>
+<meta charset="utf-8">
+<title>Conversa com {agent_name}</title>
+<style>
+body {{ background: #313338; color: #dbdee1; font-family: sans-serif; max-width: 800px; margin: 0 auto; padding: 24px; }}
+h1 {{ font-size: 20px; }}
+.exported {{ color: #949ba4; font-size: 13px; margin-bottom: 24px; }}
+.entry {{ margin-bottom: 14px; }}
+.speaker {{ font-weight: bold; margin-bottom: 2px; }}
+.agent .speaker {{ color: #f0b232; }}
+.system {{ color: #949ba4; font-style: italic; }}
+.date {{ color: #949ba4; font-size: 12px; font-weight: normal; margin-left: 8px; }}
+</style>
+</head>
+<body>
+<h1>Conversa com {agent_name}</h1>
+<div class="exported">Exportada em {exported_at} (UTC)</div>
+{body}</body>
+</html>
+"#,
+            agent_name = escape_html(&self.agent_name),
+            exported_at = escape_html(&self.exported_at),
+        )
+    }
+}
+
+fn escape_html(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
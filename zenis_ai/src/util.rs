@@ -1,21 +1,15 @@
 use chrono::Utc;
-use regex::Regex;
 use zenis_database::instance_model::{InstanceBrain, InstanceMessage, InstanceModel};
 
 use crate::{
     brain::Brain,
     claude_brain::ClaudeBrain,
-    common::{ChatMessage, ChatResponse},
+    common::{ChatMessage, ChatResponse, Role},
     gemini_brain::{GeminiBrain, GeminiModel},
     openai_brain::{OpenAIBrain, OpenAIModel},
+    transcript::{Transcript, TranscriptFormat},
 };
 
-pub fn remove_italic_actions(input: &str) -> String {
-    let re = Regex::new(r"[_*][^_*]+[_*]").unwrap();
-    let output = re.replace_all(input, "");
-    output.trim().to_string()
-}
-
 pub async fn process_instance_message_queue(
     instance: &mut InstanceModel,
     messages: Vec<ChatMessage>,
@@ -29,6 +23,7 @@ pub async fn process_instance_message_queue(
     let response = brain.prompt_raw(parameters, messages.clone()).await?;
     instance.push_message(InstanceMessage {
         image_url: None,
+        message_id: None,
         is_assistant: true,
         user_id: instance.webhook_id,
         text: response.message.content.clone(),
@@ -39,6 +34,38 @@ pub async fn process_instance_message_queue(
     Ok(response)
 }
 
+/// Summarizes a conversation so a later session can pick it up without the full history
+pub async fn summarize_history(
+    agent_name: &str,
+    history: &[InstanceMessage],
+    debug: bool,
+) -> anyhow::Result<String> {
+    let transcript = Transcript::from_history(agent_name, history);
+    if transcript.entries.is_empty() {
+        return Ok(String::new());
+    }
+
+    let brain = get_brain(InstanceBrain::GeminiFlash);
+    let mut parameters = brain.default_parameters();
+    parameters.debug = debug;
+    parameters.system_prompt = format!(
+        "Você resume conversas entre usuários do Discord e o personagem {agent_name}. Escreva um resumo curto (no máximo 10 frases) com os acontecimentos, decisões, nomes e assuntos pendentes, para que {agent_name} possa continuar a conversa depois. Responda apenas com o resumo."
+    );
+
+    let response = brain
+        .prompt_chat(
+            parameters,
+            vec![ChatMessage {
+                role: Role::User,
+                content: transcript.render(TranscriptFormat::Markdown),
+                image_url: None,
+            }],
+        )
+        .await?;
+
+    Ok(response.message.content.trim().to_owned())
+}
+
 pub fn get_brain(brain: InstanceBrain) -> Box<dyn Brain + Send + Sync + 'static> {
     match brain {
         InstanceBrain::GeminiFlash => Box::new(GeminiBrain {
@@ -1,5 +1,6 @@
 use rand::{prelude::SliceRandom, rngs::StdRng, SeedableRng};
-use zenis_database::user_model::AdminPermission;
+use zenis_database::{agent_model::AgentModel, user_model::AdminPermission};
+use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
 
 use crate::prelude::*;
 
@@ -14,6 +15,7 @@ pub enum Command {
     RemoveCredits(IdString, i64),
     ClearChannelInstances(u64, String),
     ClearInstances(String),
+    Queue,
     Accept(String),
     Reject(String, String),
     AddPerm(IdString, AdminPermission),
@@ -29,6 +31,7 @@ impl Command {
         "add credits <id> [quantity]",
         "remove credits <id> [quantity]",
         "clear instances [reason]",
+        "queue",
         "accept <id>",
         "reject <id> <reason>",
         "add perm <id> <permission>",
@@ -97,6 +100,7 @@ impl Command {
                     _ => None,
                 }
             }
+            "queue" => Some(Command::Queue),
             "accept" => {
                 let id = splitted.next()?.to_lowercase();
                 Some(Command::Accept(id))
@@ -129,6 +133,7 @@ impl Command {
             Command::RemoveCredits(_, _) => &[AdminPermission::ManageCredits],
             Command::ClearChannelInstances(_, _) => &[AdminPermission::ManageInstances],
             Command::ClearInstances(_) => &[AdminPermission::ManageInstances],
+            Command::Queue => &[AdminPermission::ManageAgents],
             Command::Accept(_) => &[AdminPermission::ManageAgents],
             Command::Reject(_, _) => &[AdminPermission::ManageAgents],
             Command::AddPerm(_, _) => &[AdminPermission::All],
@@ -355,8 +360,11 @@ pub async fn adm(
             ))
             .await?;
         }
+        Command::Queue => {
+            review_queue(&mut ctx, &author).await?;
+        }
         Command::Accept(id) => {
-            let Some(mut agent) = ctx.db().agents().get_by_identifier(id).await? else {
+            let Some(agent) = ctx.db().agents().get_by_identifier(id).await? else {
                 ctx.send(
                     Response::new_user_reply(&author, "agente inválido ou inexistente")
                         .add_emoji_prefix(emojis::ERROR),
@@ -365,53 +373,10 @@ pub async fn adm(
                 return Ok(());
             };
 
-            if !agent.is_waiting_for_approval {
-                ctx.send(
-                    Response::new_user_reply(
-                        &author,
-                        "o agente não está em processo de verificação.",
-                    )
-                    .add_emoji_prefix(emojis::ERROR),
-                )
-                .await?;
-                return Ok(());
-            }
-
-            agent.is_waiting_for_approval = false;
-            agent.public = true;
-            ctx.db().agents().save(agent.clone()).await?;
-
-            ctx.send(
-                Response::new_user_reply(&author, "agente aceito com sucesso!")
-                    .add_emoji_prefix(emojis::SUCCESS),
-            )
-            .await
-            .ok();
-
-            let dm = ctx
-                .client
-                .http
-                .create_private_channel(Id::new(agent.creator_user_id))
-                .await?
-                .model()
-                .await?;
-            let embed = EmbedBuilder::new_common()
-                .set_color(Color::GREEN)
-                .set_author(EmbedAuthor {
-                    name: "Agente aceito!".to_string(),
-                    icon_url: agent.agent_url_image.clone(),
-                })
-                .set_description(format!("## {} Agente aceito!\nO seu agente **{}** (`{}`) foi aceito e agora é PÚBLICO. Parabéns!", emojis::SUCCESS, agent.name, agent.identifier));
-
-            ctx.client
-                .http
-                .create_message(dm.id)
-                .embeds(&[embed.build()])
-                .await
-                .ok();
+            decide_approval(&mut ctx, &author, agent, true, None).await?;
         }
         Command::Reject(id, reason) => {
-            let Some(mut agent) = ctx.db().agents().get_by_identifier(id).await? else {
+            let Some(agent) = ctx.db().agents().get_by_identifier(id).await? else {
                 ctx.send(
                     Response::new_user_reply(&author, "agente inválido ou inexistente")
                         .add_emoji_prefix(emojis::ERROR),
@@ -420,51 +385,7 @@ pub async fn adm(
                 return Ok(());
             };
 
-            if !agent.is_waiting_for_approval {
-                ctx.send(
-                    Response::new_user_reply(
-                        &author,
-                        "o agente não está em processo de verificação.",
-                    )
-                    .add_emoji_prefix(emojis::ERROR),
-                )
-                .await?;
-                return Ok(());
-            }
-
-            agent.is_waiting_for_approval = false;
-            agent.public = false;
-            ctx.db().agents().save(agent.clone()).await?;
-
-            ctx.send(
-                Response::new_user_reply(&author, "agente rejeitado com sucesso!")
-                    .add_emoji_prefix(emojis::SUCCESS),
-            )
-            .await
-            .ok();
-
-            let dm = ctx
-                .client
-                .http
-                .create_private_channel(Id::new(agent.creator_user_id))
-                .await?
-                .model()
-                .await?;
-            let embed = EmbedBuilder::new_common()
-                .set_color(Color::RED)
-                .set_author(EmbedAuthor {
-                    name: "Agente rejeitado!".to_string(),
-                    icon_url: agent.agent_url_image.clone(),
-                })
-                .set_description(format!("## {} Agente rejeitado!\nO seu agente **{}** (`{}`) foi rejeitado.\n**Motivo da recusa:** `{}`", emojis::ERROR, agent.name, agent.identifier, reason))
-                .add_footer_text("Não acha que a recusa foi justa? Entre no /servidoroficial e fale com o suporte do bot.");
-
-            ctx.client
-                .http
-                .create_message(dm.id)
-                .embeds(&[embed.build()])
-                .await
-                .ok();
+            decide_approval(&mut ctx, &author, agent, false, Some(reason)).await?;
         }
         Command::AddPerm(id, permission) => {
             let id = parse_id!(Some(id));
@@ -565,3 +486,341 @@ pub async fn adm(
 
     Ok(())
 }
+
+/// Lists the agents waiting for approval, letting the admin take, approve or reject one of them
+async fn review_queue(ctx: &mut CommandContext, author: &User) -> anyhow::Result<()> {
+    let agents = ctx.db().agents().get_all_waiting_for_approval().await?;
+    if agents.is_empty() {
+        ctx.reply(
+            Response::new_user_reply(author, "não há agentes esperando por verificação!")
+                .add_emoji_prefix(emojis::SUCCESS),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let pages = agents
+        .iter()
+        .enumerate()
+        .map(|(index, agent)| make_review_embed(agent, index, agents.len()))
+        .collect::<Vec<_>>();
+
+    let Some(action) = EmbedPagination::new(ctx.clone(), pages)
+        .add_extra_button(
+            ButtonBuilder::new()
+                .set_custom_id("assign")
+                .set_label("Assumir")
+                .set_emoji(Emoji::from_unicode("🙋"))
+                .set_style(ButtonStyle::Secondary),
+        )
+        .add_extra_button(
+            ButtonBuilder::new()
+                .set_custom_id("approve")
+                .set_label("Aprovar")
+                .set_style(ButtonStyle::Success),
+        )
+        .add_extra_button(
+            ButtonBuilder::new()
+                .set_custom_id("reject")
+                .set_label("Rejeitar")
+                .set_style(ButtonStyle::Danger),
+        )
+        .send()
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let mut ctx = action.ctx;
+    let Some(agent) = agents.get(action.page) else {
+        return Ok(());
+    };
+
+    // Another admin may have acted on the agent while the queue was open
+    let Some(mut agent) = ctx
+        .db()
+        .agents()
+        .get_by_identifier(&agent.identifier)
+        .await?
+    else {
+        ctx.send(
+            Response::new_user_reply(author, "agente inválido ou inexistente")
+                .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    match action.custom_id.as_str() {
+        "assign" => {
+            if !agent.is_waiting_for_approval {
+                ctx.send(
+                    Response::new_user_reply(
+                        author,
+                        "o agente não está em processo de verificação.",
+                    )
+                    .add_emoji_prefix(emojis::ERROR),
+                )
+                .await?;
+                return Ok(());
+            }
+
+            agent.reviewer_user_id = Some(author.id.get());
+            ctx.db().agents().save(agent.clone()).await?;
+
+            ctx.send(
+                Response::new_user_reply(
+                    author,
+                    format!("agora você é o revisor de **{}**!", agent.name),
+                )
+                .add_emoji_prefix(emojis::SUCCESS),
+            )
+            .await?;
+        }
+        "approve" => decide_approval(&mut ctx, author, agent, true, None).await?,
+        "reject" => {
+            let Some(reason) = ask_for_rejection_reason(&mut ctx, author).await? else {
+                return Ok(());
+            };
+
+            decide_approval(&mut ctx, author, agent, false, Some(reason)).await?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn make_review_embed(agent: &AgentModel, index: usize, total: usize) -> EmbedBuilder {
+    let mut embed = EmbedBuilder::new_common()
+        .set_color(Color::YELLOW)
+        .set_author(EmbedAuthor {
+            name: format!("Fila de verificação ({}/{})", index + 1, total),
+            icon_url: agent.agent_url_image.clone(),
+        })
+        .set_title(&agent.name)
+        .set_description(format!("```{}```", agent.description))
+        .add_inlined_field(
+            "Informações",
+            format!(
+                "**ID**: `{}`\n**Criador**: <@{}>\n**Revisor**: {}{}",
+                agent.identifier,
+                agent.creator_user_id,
+                match agent.reviewer_user_id {
+                    Some(reviewer_user_id) => format!("<@{reviewer_user_id}>"),
+                    None => "`Ninguém`".to_string(),
+                },
+                match &agent.forked_from {
+                    Some(lineage) => format!("\n**Baseado em**: `{}`", lineage.identifier),
+                    None => String::new(),
+                }
+            ),
+        )
+        .add_inlined_field(
+            "📈 Preços",
+            format!(
+                "**Invocação**: `{}₢`\n**Resposta**: `{}₢`",
+                agent.pricing.price_per_invocation, agent.pricing.price_per_reply
+            ),
+        )
+        .add_not_inlined_field(
+            "📢 Mensagem de introdução",
+            format!("`{}`", agent.introduction_message),
+        )
+        .add_inlined_field(
+            "🏷️ Tags",
+            if agent.tags.is_empty() {
+                "`Nenhuma`".to_string()
+            } else {
+                agent
+                    .tags
+                    .iter()
+                    .map(|tag| format!("`{tag}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            },
+        )
+        .add_inlined_field(
+            "📚 Extras",
+            format!(
+                "**Exemplos de diálogo**: `{}`\n**Entradas de lore**: `{}`",
+                agent.example_dialogues.len(),
+                agent.lore.len()
+            ),
+        );
+
+    if let Some(dialogue) = agent.example_dialogues.first() {
+        embed = embed.add_not_inlined_field(
+            "💬 Exemplo de diálogo",
+            format!(
+                "**Usuário**: {}\n**{}**: {}",
+                dialogue.user_message.chars().take(400).collect::<String>(),
+                agent.name,
+                dialogue.agent_message.chars().take(400).collect::<String>()
+            ),
+        );
+    }
+
+    if !agent.moderation_history.is_empty() {
+        let history = agent
+            .moderation_history
+            .iter()
+            .rev()
+            .take(5)
+            .map(|decision| {
+                format!(
+                    "{} <t:{}:d> por <@{}>{}",
+                    if decision.approved { "✅" } else { "❌" },
+                    decision.decided_at_timestamp,
+                    decision.reviewer_user_id,
+                    match &decision.reason {
+                        Some(reason) => format!(": `{reason}`"),
+                        None => String::new(),
+                    }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        embed = embed.add_not_inlined_field("📜 Histórico de decisões", history);
+    }
+
+    if let Some(image_url) = &agent.agent_url_image {
+        embed = embed.set_thumbnail(image_url);
+    }
+
+    embed
+}
+
+async fn ask_for_rejection_reason(
+    ctx: &mut CommandContext,
+    author: &User,
+) -> anyhow::Result<Option<String>> {
+    let mut buttons = config::AGENT_REJECTION_REASONS
+        .iter()
+        .enumerate()
+        .map(|(index, reason)| {
+            ButtonBuilder::new()
+                .set_custom_id(index.to_string())
+                .set_label(*reason)
+                .set_style(ButtonStyle::Secondary)
+        })
+        .collect::<Vec<_>>();
+    buttons.push(
+        ButtonBuilder::new()
+            .set_custom_id("other")
+            .set_label("Outro motivo")
+            .set_style(ButtonStyle::Secondary),
+    );
+
+    let Some(custom_id) = await_button_choice(
+        ctx,
+        author,
+        Response::new_user_reply(author, "qual o motivo da recusa?").add_emoji_prefix("❔"),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    if let Some(reason) = custom_id
+        .parse::<usize>()
+        .ok()
+        .and_then(|index| config::AGENT_REJECTION_REASONS.get(index))
+    {
+        return Ok(Some(reason.to_string()));
+    }
+
+    let reason = get_input(
+        ctx,
+        author,
+        Response::new_user_reply(author, "escreva no chat o motivo da recusa:")
+            .add_emoji_prefix("✏️"),
+    )
+    .await?;
+
+    Ok(reason.filter(|reason| !reason.is_empty()))
+}
+
+/// Approves or rejects a pending publish request, recording the decision and notifying the creator by DM
+async fn decide_approval(
+    ctx: &mut CommandContext,
+    author: &User,
+    mut agent: AgentModel,
+    approved: bool,
+    reason: Option<String>,
+) -> anyhow::Result<()> {
+    if !agent.is_waiting_for_approval {
+        ctx.send(
+            Response::new_user_reply(author, "o agente não está em processo de verificação.")
+                .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if let Some(reviewer_user_id) = agent.reviewer_user_id.filter(|id| *id != author.id.get()) {
+        ctx.send(
+            Response::new_user_reply(
+                author,
+                format!("o agente está sendo revisado por <@{reviewer_user_id}>! Assuma o agente na fila (**/adm cmd: queue**) para decidir por ele."),
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    agent.decide_approval(author.id.get(), approved, reason.clone());
+    ctx.db().agents().save(agent.clone()).await?;
+
+    ctx.send(
+        Response::new_user_reply(
+            author,
+            if approved {
+                "agente aceito com sucesso!"
+            } else {
+                "agente rejeitado com sucesso!"
+            },
+        )
+        .add_emoji_prefix(emojis::SUCCESS),
+    )
+    .await
+    .ok();
+
+    let embed = if approved {
+        EmbedBuilder::new_common()
+            .set_color(Color::GREEN)
+            .set_author(EmbedAuthor {
+                name: "Agente aceito!".to_string(),
+                icon_url: agent.agent_url_image.clone(),
+            })
+            .set_description(format!("## {} Agente aceito!\nO seu agente **{}** (`{}`) foi aceito e agora é PÚBLICO. Parabéns!", emojis::SUCCESS, agent.name, agent.identifier))
+    } else {
+        EmbedBuilder::new_common()
+            .set_color(Color::RED)
+            .set_author(EmbedAuthor {
+                name: "Agente rejeitado!".to_string(),
+                icon_url: agent.agent_url_image.clone(),
+            })
+            .set_description(format!("## {} Agente rejeitado!\nO seu agente **{}** (`{}`) foi rejeitado.\n**Motivo da recusa:** `{}`", emojis::ERROR, agent.name, agent.identifier, reason.unwrap_or_default()))
+            .add_footer_text("Não acha que a recusa foi justa? Entre no /servidoroficial e fale com o suporte do bot.")
+    };
+
+    let dm = ctx
+        .client
+        .http
+        .create_private_channel(Id::new(agent.creator_user_id))
+        .await?
+        .model()
+        .await?;
+
+    ctx.client
+        .http
+        .create_message(dm.id)
+        .embeds(&[embed.build()])
+        .await
+        .ok();
+
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use zenis_database::{
+    agent_model::AgentModel,
+    agent_version_model::{AgentVersionContent, AgentVersionField, AgentVersionModel},
+};
+use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
+
+use crate::prelude::*;
+
+/// Versions listed in the history, most recent first
+const MAX_LISTED_VERSIONS: usize = 25;
+/// Unchanged words kept around each change in a text diff
+const DIFF_CONTEXT_WORDS: usize = 6;
+
+/// Saves a change the creator made to the agent, recording it as a new version. Approved public agents
+/// go back to verification (and stay private until approved) when their description changes
+pub(crate) async fn save_agent_change(
+    ctx: &mut CommandContext,
+    author: &User,
+    mut agent: AgentModel,
+    restored_from: Option<u32>,
+) -> anyhow::Result<()> {
+    let Some(before) = ctx
+        .db()
+        .agents()
+        .get_by_identifier(&agent.identifier)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let changed_fields = AgentVersionContent::from_agent(&before)
+        .changed_fields(&AgentVersionContent::from_agent(&agent));
+
+    let needs_approval = before.public && changed_fields.contains(&AgentVersionField::Description);
+    if needs_approval {
+        agent.public = false;
+        agent.submit_for_approval();
+    }
+
+    // The version is only recorded once the change was really applied
+    ctx.db().agents().save(agent.clone()).await?;
+    ctx.db()
+        .agent_versions()
+        .record_change(&before, &agent, author.id.get(), restored_from)
+        .await?;
+
+    if needs_approval {
+        ctx.send(
+            Response::new_user_reply(
+                author,
+                "como a descrição mudou, o agente voltou para o processo de verificação e ficará privado até ser aprovado novamente.",
+            )
+            .add_emoji_prefix(emojis::WARNING),
+        )
+        .await?;
+
+        ctx.client.emit_request_hook(agent).await?;
+    }
+
+    Ok(())
+}
+
+/// Shows the versions of the agent with what changed in each one, letting the creator restore one of them
+pub(crate) async fn show_version_history(
+    ctx: &mut CommandContext,
+    author: &User,
+    identifier: &str,
+) -> anyhow::Result<()> {
+    let versions = ctx
+        .db()
+        .agent_versions()
+        .get_all_by_agent(&identifier.to_lowercase())
+        .await?;
+
+    if versions.is_empty() {
+        ctx.send(
+            Response::new_user_reply(
+                author,
+                "o agente ainda não tem versões salvas! Uma versão é salva sempre que você altera o agente.",
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let pages = versions
+        .iter()
+        .take(MAX_LISTED_VERSIONS)
+        .enumerate()
+        .map(|(index, version)| make_version_embed(version, versions.get(index + 1), index == 0))
+        .collect::<Vec<_>>();
+
+    let Some(action) = EmbedPagination::new(ctx.clone(), pages)
+        .add_extra_button(
+            ButtonBuilder::new()
+                .set_custom_id("restore")
+                .set_label("Restaurar esta versão")
+                .set_emoji(Emoji::from_unicode("⏪"))
+                .set_style(ButtonStyle::Secondary),
+        )
+        .send()
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let mut ctx = action.ctx;
+    let Some(version) = versions.get(action.page) else {
+        return Ok(());
+    };
+
+    if action.page == 0 {
+        ctx.send(
+            Response::new_user_reply(author, "essa já é a versão atual do agente!")
+                .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let confirmation = ctx
+        .helper()
+        .create_confirmation(
+            author.id,
+            false,
+            Response::new_user_reply(
+                author,
+                format!(
+                    "você quer restaurar o agente para a **versão #{}**? A versão atual continua salva no histórico.",
+                    version.number
+                ),
+            )
+            .add_emoji_prefix(emojis::WARNING),
+        )
+        .await?;
+    if !confirmation {
+        return Ok(());
+    }
+
+    let Some(mut agent) = ctx.db().agents().get_by_identifier(identifier).await? else {
+        ctx.send(
+            Response::new_user_reply(author, "agente inválido ou inexistente")
+                .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    version.content.apply_to(&mut agent);
+    save_agent_change(&mut ctx, author, agent, Some(version.number)).await?;
+
+    ctx.send(
+        Response::new_user_reply(
+            author,
+            format!(
+                "agente restaurado para a **versão #{}** com sucesso! A mudança vale para as próximas invocações.",
+                version.number
+            ),
+        )
+        .add_emoji_prefix(emojis::SUCCESS),
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn make_version_embed(
+    version: &AgentVersionModel,
+    previous: Option<&AgentVersionModel>,
+    is_current: bool,
+) -> EmbedBuilder {
+    let summary = match (previous, version.restored_from) {
+        (None, _) => "Versão inicial".to_string(),
+        (Some(_), Some(restored_from)) => format!("Restauração da versão #{restored_from}"),
+        (Some(_), None) => version
+            .changed_fields
+            .iter()
+            .map(|field| field.name())
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+
+    let mut embed = EmbedBuilder::new_common()
+        .set_color(if is_current {
+            Color::GREEN
+        } else {
+            Color::YELLOW
+        })
+        .set_title(format!(
+            "Versão #{}{}",
+            version.number,
+            if is_current { " (atual)" } else { "" }
+        ))
+        .set_description(format!(
+            "**Autor**: <@{}>\n**Data**: <t:{}:f>\n**Alterações**: {}",
+            version.author_user_id, version.created_at_timestamp, summary
+        ));
+
+    let Some(previous) = previous else {
+        return embed;
+    };
+
+    for field in version.changed_fields.iter() {
+        embed = embed.add_not_inlined_field(
+            field.name(),
+            format_field_diff(*field, &previous.content, &version.content),
+        );
+    }
+
+    embed
+}
+
+fn format_field_diff(
+    field: AgentVersionField,
+    old: &AgentVersionContent,
+    new: &AgentVersionContent,
+) -> String {
+    let diff = match field {
+        AgentVersionField::Description => format_text_diff(&old.description, &new.description),
+        AgentVersionField::IntroductionMessage => {
+            format_text_diff(&old.introduction_message, &new.introduction_message)
+        }
+        AgentVersionField::Image => format!(
+            "{} → {}",
+            old.agent_url_image.as_deref().unwrap_or("Sem imagem"),
+            new.agent_url_image.as_deref().unwrap_or("Sem imagem")
+        ),
+        AgentVersionField::Pricing => format!(
+            "**Invocação**: `{}₢` → `{}₢`\n**Resposta**: `{}₢` → `{}₢`",
+            old.pricing.price_per_invocation,
+            new.pricing.price_per_invocation,
+            old.pricing.price_per_reply,
+            new.pricing.price_per_reply
+        ),
+        AgentVersionField::ExampleDialogues => format!(
+            "`{}` → `{}` exemplos",
+            old.example_dialogues.len(),
+            new.example_dialogues.len()
+        ),
+    };
+
+    // Discord fields hold at most 1024 characters
+    if diff.chars().count() > 1000 {
+        format!("{}...", diff.chars().take(1000).collect::<String>())
+    } else {
+        diff
+    }
+}
+
+/// Removed words are struck through and added ones are bold, with long unchanged parts shortened
+fn format_text_diff(old: &str, new: &str) -> String {
+    let chunks = diff_words(old, new);
+    let last_index = chunks.len().saturating_sub(1);
+
+    let mut formatted = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        match chunk {
+            DiffChunk::Equal(text) => {
+                let words = text
+                    .split_inclusive(char::is_whitespace)
+                    .collect::<Vec<_>>();
+                if words.len() <= DIFF_CONTEXT_WORDS * 2 {
+                    formatted.push_str(text);
+                    continue;
+                }
+
+                // Only the words next to a change are kept
+                if index > 0 {
+                    formatted.push_str(&words[..DIFF_CONTEXT_WORDS].concat());
+                }
+                formatted.push_str("... ");
+                if index < last_index {
+                    formatted.push_str(&words[words.len() - DIFF_CONTEXT_WORDS..].concat());
+                }
+            }
+            DiffChunk::Removed(text) => formatted.push_str(&format!("~~{}~~ ", text.trim())),
+            DiffChunk::Added(text) => formatted.push_str(&format!("**{}** ", text.trim())),
+        }
+    }
+
+    formatted.trim().to_string()
+}
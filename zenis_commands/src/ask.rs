@@ -0,0 +1,278 @@
+use chrono::Utc;
+use zenis_ai::{
+    brain::make_agent_examples_prompt,
+    common::{ChatMessage, Role},
+    template::to_assistant_object,
+    util::get_brain,
+};
+use zenis_database::{
+    agent_model::AgentModel,
+    earning_model::EarningSource,
+    guild_model::GuildSettings,
+    instance_model::{CreditsPaymentMethod, InstanceBrain},
+};
+
+use crate::{invoke::guild_policy_prompt, prelude::*};
+
+const QUESTION_BRAIN: InstanceBrain = InstanceBrain::GeminiFlash;
+
+#[command("Faça uma pergunta rápida para um agente, sem invocá-lo no chat!")]
+#[name("perguntar")]
+pub async fn ask(
+    mut ctx: CommandContext,
+    #[rename("agente")]
+    #[description("O ID do agente que vai responder")]
+    #[autocomplete(suggest_agent_ids)]
+    identifier: String,
+    #[rename("pergunta")]
+    #[description("A pergunta que você quer fazer ao agente")]
+    #[min_max_length(1, 1000)]
+    question: String,
+    #[rename("público")]
+    #[description("Mostrar a resposta para todos no chat (padrão: não)")]
+    public: Option<bool>,
+) -> anyhow::Result<()> {
+    let author = ctx.author().await?;
+
+    let Some(agent) = ctx.db().agents().get_by_identifier(&identifier).await? else {
+        ctx.reply(
+            Response::new_user_reply(&author, "agente inválido ou inexistente")
+                .add_emoji_prefix(emojis::ERROR)
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    answer_question(&mut ctx, &author, agent, question, public.unwrap_or(false)).await
+}
+
+pub(crate) async fn answer_question(
+    ctx: &mut CommandContext,
+    author: &User,
+    agent: AgentModel,
+    question: String,
+    public: bool,
+) -> anyhow::Result<()> {
+    if !agent.public
+        && agent.creator_user_id != author.id.get()
+        && agent.guild_id != ctx.interaction.guild_id.map(|g| g.get())
+    {
+        ctx.reply(
+            Response::new_user_reply(
+                author,
+                "você não tem permissão para perguntar a este agente privado!",
+            )
+            .add_emoji_prefix(emojis::ERROR)
+            .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let brain = QUESTION_BRAIN;
+    let mut pricing = agent.pricing;
+    pricing.price_per_reply += brain.extra_price_per_reply();
+    let price = pricing.price_per_question();
+
+    let user_data = ctx.db().users().get_by_user(author.id).await?;
+    if user_data.credits < price {
+        ctx.reply(
+            Response::new_user_reply(
+                author,
+                format!(
+                    "você precisa de **{price}₢** na sua carteira para perguntar a **{}**! Use **/comprar**.",
+                    agent.name
+                ),
+            )
+            .add_emoji_prefix(emojis::ERROR)
+            .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let thinking = Response::new_user_reply(author, format!("**{}** está pensando...", agent.name))
+        .add_emoji_prefix("💭");
+    ctx.reply(if public {
+        thinking
+    } else {
+        thinking.set_ephemeral()
+    })
+    .await?;
+
+    let guild_name = match ctx.interaction.guild_id {
+        Some(guild_id) => ctx
+            .client
+            .get_guild(guild_id)
+            .await
+            .map(|g| g.name)
+            .unwrap_or_default(),
+        None => String::from("Mensagem direta"),
+    };
+
+    let guild_settings = match ctx.interaction.guild_id {
+        Some(guild_id) => ctx.db().guilds().get_by_guild(guild_id).await?.settings,
+        None => GuildSettings::default(),
+    };
+    let is_nsfw_channel = ctx
+        .interaction
+        .channel
+        .as_ref()
+        .and_then(|channel| channel.nsfw)
+        .unwrap_or(false);
+
+    let system_prompt = format!(
+        "[Zenis Agent System 1.0]\n<!agent_name/>{}\n<!agent_description/>{}\n<!guild>{}\n{}\n<!system_instruction/>Esta é uma pergunta única: responda em uma só mensagem, sem aguardar e sem sair da conversa.{}",
+        agent.name,
+        agent.description,
+        guild_name,
+        guild_policy_prompt(&guild_settings, is_nsfw_channel),
+        make_agent_examples_prompt(&agent.name, &agent.example_dialogues)
+    );
+
+    let messages = vec![ChatMessage {
+        role: Role::User,
+        content: format!(
+            "<!name/>{}\n<!user/>@{}\n<!user_id/>{}\n<!date/>{}\n<!message/>{}",
+            author.display_name(),
+            author.name,
+            author.id,
+            guild_settings.format_date(Utc::now()),
+            question
+        ),
+        image_url: None,
+    }];
+
+    let brain_impl = get_brain(brain);
+    let mut parameters = brain_impl.default_parameters();
+    parameters.debug = config::DEBUG;
+    parameters.system_prompt = system_prompt;
+
+    let response = match brain_impl.prompt_chat(parameters, messages).await {
+        Ok(response) => response,
+        Err(e) => {
+            ctx.client
+                .emit_error_hook(
+                    format!(
+                        "One-shot question failed. Agent ID: {}, brain used: {}",
+                        agent.identifier,
+                        brain.name()
+                    ),
+                    e,
+                )
+                .await
+                .ok();
+
+            ctx.update_interaction_reply(
+                Response::new_user_reply(
+                    author,
+                    "algo deu errado ao perguntar ao agente! Você não foi cobrado.",
+                )
+                .add_emoji_prefix(emojis::ERROR),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let assistant_object = to_assistant_object(&response.message.content);
+    // Brains sometimes skip the template, but its tags must never reach the user
+    let answer = match assistant_object.message {
+        _ if assistant_object.is_noreply || assistant_object.exit_reason.is_some() => None,
+        Some(message) => Some(message),
+        None if !response.message.content.contains("<!") => Some(response.message.content),
+        None => None,
+    }
+    .map(|answer| answer.trim().to_owned())
+    .filter(|answer| !answer.is_empty());
+
+    let Some(answer) = answer else {
+        ctx.update_interaction_reply(
+            Response::new_user_reply(
+                author,
+                format!(
+                    "**{}** não quis responder a essa pergunta. Você não foi cobrado.",
+                    agent.name
+                ),
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    };
+    let answer = answer.chars().take(4000).collect::<String>();
+
+    // The balance may have changed while the agent was thinking, so the charge only happens if it still covers the price
+    if !ctx
+        .db()
+        .users()
+        .try_remove_credits(author.id, price)
+        .await?
+    {
+        ctx.update_interaction_reply(
+            Response::new_user_reply(
+                author,
+                format!("você não tem mais **{price}₢** na sua carteira para pagar a resposta! Você não foi cobrado."),
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let earnings = ctx.db().earnings();
+    let payer = CreditsPaymentMethod::UserCredits(author.id.get());
+    earnings
+        .credit_creators(
+            &agent,
+            EarningSource::Invocation,
+            pricing.price_per_invocation,
+            payer,
+        )
+        .await?;
+    earnings
+        .credit_creators(
+            &agent,
+            EarningSource::Reply,
+            agent.pricing.price_per_reply,
+            payer,
+        )
+        .await?;
+
+    if let Ok(Some(mut agent)) = ctx.db().agents().get_by_identifier(&agent.identifier).await {
+        agent.stats.replies += 1;
+        ctx.db().agents().save(agent).await.ok();
+    }
+
+    let mut display_question = question.chars().take(1000).collect::<String>();
+    if question.chars().count() > 1000 {
+        display_question.push_str("...");
+    }
+
+    let mut embed = EmbedBuilder::new_common()
+        .set_color(Color::LIGHT_ORANGE)
+        .set_author(EmbedAuthor {
+            name: agent.name.clone(),
+            icon_url: agent.agent_url_image.clone(),
+        })
+        .set_description(answer)
+        .add_not_inlined_field(
+            format!("❔ Pergunta de {}", author.display_name()),
+            display_question,
+        )
+        .add_footer_text(format!(
+            "ID do agente: {} | Custo: {price}₢ | Cérebro: {}",
+            agent.identifier,
+            brain.name()
+        ));
+
+    if let Some(image_url) = &agent.agent_url_image {
+        embed = embed.set_thumbnail(image_url);
+    }
+
+    ctx.update_interaction_reply(Response::from(embed).add_string_content(String::new()))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use zenis_discord::twilight_model::channel::message::component::{TextInput, TextInputStyle};
+use zenis_framework::watcher::WatcherOptions;
+
+use crate::{ask::answer_question, prelude::*};
+
+#[command("Pergunte a um agente sobre esta mensagem")]
+#[name("Perguntar a um agente")]
+#[kind("message")]
+pub async fn ask_about_message(mut ctx: CommandContext) -> anyhow::Result<()> {
+    let author = ctx.author().await?;
+    let Some(message) = ctx.target_message() else {
+        return Ok(());
+    };
+
+    if message.content.trim().is_empty() {
+        ctx.reply(
+            Response::new_user_reply(&author, "essa mensagem não tem nenhum texto!")
+                .add_emoji_prefix(emojis::ERROR)
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let modal = ModalBuilder::new("Perguntar a um agente", format!("ask_{}", message.id))
+        .add_text_input(TextInput {
+            custom_id: "agent".to_string(),
+            label: "ID do agente".to_string(),
+            max_length: Some(64),
+            min_length: Some(1),
+            placeholder: Some("zenis".to_string()),
+            required: Some(true),
+            style: TextInputStyle::Short,
+            value: None,
+        })
+        .add_text_input(TextInput {
+            custom_id: "question".to_string(),
+            label: "Pergunta (opcional)".to_string(),
+            max_length: Some(500),
+            min_length: None,
+            placeholder: Some("O que você acha disso?".to_string()),
+            required: Some(false),
+            style: TextInputStyle::Paragraph,
+            value: None,
+        });
+
+    let Some(modal_response) = ctx
+        .helper()
+        .show_and_await_modal(
+            modal,
+            WatcherOptions {
+                timeout: Duration::from_secs(300),
+            },
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let identifier = modal_response.get_text_input("agent").unwrap_or_default();
+    let extra_question = modal_response
+        .get_text_input("question")
+        .unwrap_or_default();
+
+    let mut ctx = CommandContext::from_with_interaction(&ctx, modal_response.interaction());
+
+    let Some(agent) = ctx
+        .db()
+        .agents()
+        .get_by_identifier(identifier.trim())
+        .await?
+    else {
+        ctx.reply(
+            Response::new_user_reply(&author, "agente inválido ou inexistente")
+                .add_emoji_prefix(emojis::ERROR)
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let content = message.content.chars().take(800).collect::<String>();
+    let mut question = format!(
+        "Sobre esta mensagem de {}:\n\"{}\"",
+        message.author.display_name(),
+        content
+    );
+    if !extra_question.trim().is_empty() {
+        question.push_str(&format!("\n\n{}", extra_question.trim()));
+    }
+
+    answer_question(&mut ctx, &author, agent, question, false).await
+}
@@ -3,7 +3,12 @@ use std::time::Duration;
 use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
 use zenis_framework::{util::make_multiple_rows, watcher::WatcherOptions};
 
-use crate::prelude::*;
+use zenis_database::agent_model::AgentExampleDialogue;
+
+use crate::{
+    agent_versions::{save_agent_change, show_version_history},
+    prelude::*,
+};
 
 #[command("Configure um dos seus agentes!")]
 #[name("configurar agente")]
@@ -11,6 +16,7 @@ pub async fn configure_agent(
     mut ctx: CommandContext,
     #[rename("id")]
     #[description("O ID do agente que você quer configurar")]
+    #[autocomplete(suggest_own_agent_ids)]
     identifier: String,
 ) -> anyhow::Result<()> {
     let author = ctx.author().await?;
@@ -57,10 +63,18 @@ pub async fn configure_agent(
         .add_inlined_field(
             format!("{} Preços", emojis::CREDIT),
             format!(
-                "Preço por invocação: **{}₢**\nPreço por resposta: **{}₢**",
+                "Preço por invocação: **{}₢**\nPreço por resposta: **{}₢** (sem o custo do cérebro)",
                 agent.pricing.price_per_invocation, agent.pricing.price_per_reply
             ),
         )
+        .add_inlined_field(
+            "💬 Exemplos de diálogo",
+            format!(
+                "**{}** de {}",
+                agent.example_dialogues.len(),
+                config::MAX_EXAMPLE_DIALOGUES
+            ),
+        )
         .add_inlined_field(
             "👥 Agente Público?",
             if agent.public { "Sim" } else { "Não" },
@@ -92,6 +106,18 @@ pub async fn configure_agent(
             .set_custom_id("change_invocation_price")
             .set_label("Alterar Preço de Invocação")
             .set_style(ButtonStyle::Secondary),
+        ButtonBuilder::new()
+            .set_custom_id("change_reply_price")
+            .set_label("Alterar Preço por Resposta")
+            .set_style(ButtonStyle::Secondary),
+        ButtonBuilder::new()
+            .set_custom_id("change_examples")
+            .set_label("Alterar Exemplos de Diálogo")
+            .set_style(ButtonStyle::Secondary),
+        ButtonBuilder::new()
+            .set_custom_id("version_history")
+            .set_label("Histórico de Versões")
+            .set_style(ButtonStyle::Secondary),
         ButtonBuilder::new()
             .set_custom_id("change_public")
             .set_label(if !agent.public { "Publicar" } else { "Privar" })
@@ -176,7 +202,7 @@ pub async fn configure_agent(
         }
 
         agent.description = description;
-        ctx.db().agents().save(agent).await?;
+        save_agent_change(&mut ctx, &author, agent, None).await?;
 
         ctx.send(
             Response::new_user_reply(&author, "descrição alterada com sucesso!")
@@ -219,7 +245,7 @@ pub async fn configure_agent(
         }
 
         agent.introduction_message = introduction_message;
-        ctx.db().agents().save(agent).await?;
+        save_agent_change(&mut ctx, &author, agent, None).await?;
 
         ctx.send(
             Response::new_user_reply(&author, "introdução alterada com sucesso!")
@@ -246,20 +272,81 @@ pub async fn configure_agent(
             return Ok(());
         };
 
-        let price = invocation_price
-            .parse::<u8>()
-            .ok()
-            .unwrap_or(0)
-            .clamp(0, 100);
+        let price = (invocation_price.parse::<u8>().ok().unwrap_or(0) as i64).clamp(
+            *config::PRICE_PER_INVOCATION_RANGE.start(),
+            *config::PRICE_PER_INVOCATION_RANGE.end(),
+        );
 
-        agent.pricing.price_per_invocation = price as i64;
-        ctx.db().agents().save(agent).await?;
+        agent.pricing.price_per_invocation = price;
+        save_agent_change(&mut ctx, &author, agent, None).await?;
 
         ctx.send(
             Response::new_user_reply(&author, "preço alterado com sucesso!")
                 .add_emoji_prefix(emojis::SUCCESS),
         )
         .await?;
+    } else if data.custom_id == "change_reply_price" {
+        let Ok(Some(reply_price)) = get_input(
+            &mut ctx,
+            &author,
+            Response::new_user_reply(
+                &author,
+                format!(
+                    "escreva o novo preço por resposta do agente, entre **{}₢** e **{}₢** (o custo do cérebro escolhido na invocação é somado a ele):",
+                    config::PRICE_PER_REPLY_RANGE.start(),
+                    config::PRICE_PER_REPLY_RANGE.end()
+                ),
+            ),
+        )
+        .await
+        else {
+            return Ok(());
+        };
+
+        let Some(price) = reply_price
+            .parse::<i64>()
+            .ok()
+            .filter(|price| config::PRICE_PER_REPLY_RANGE.contains(price))
+        else {
+            ctx.send(
+                Response::new_user_reply(
+                    &author,
+                    format!(
+                        "o preço por resposta deve ser um número entre {} e {}!",
+                        config::PRICE_PER_REPLY_RANGE.start(),
+                        config::PRICE_PER_REPLY_RANGE.end()
+                    ),
+                )
+                .add_emoji_prefix(emojis::ERROR),
+            )
+            .await?;
+            return Ok(());
+        };
+
+        let Some(mut agent) = ctx.db().agents().get_by_identifier(identifier).await? else {
+            ctx.send(
+                Response::new_user_reply(&author, "agente inválido ou inexistente")
+                    .add_emoji_prefix(emojis::ERROR),
+            )
+            .await?;
+            return Ok(());
+        };
+
+        agent.pricing.price_per_reply = price;
+        save_agent_change(&mut ctx, &author, agent, None).await?;
+
+        ctx.send(
+            Response::new_user_reply(
+                &author,
+                "preço alterado com sucesso! O novo preço vale para as próximas invocações, agentes que já estão invocados continuam com o preço antigo.",
+            )
+            .add_emoji_prefix(emojis::SUCCESS),
+        )
+        .await?;
+    } else if data.custom_id == "change_examples" {
+        configure_example_dialogues(&mut ctx, &author, &identifier).await?;
+    } else if data.custom_id == "version_history" {
+        show_version_history(&mut ctx, &author, &identifier).await?;
     } else if data.custom_id == "change_image" {
         let Ok(Some(image)) = get_input(
             &mut ctx,
@@ -290,7 +377,7 @@ pub async fn configure_agent(
         };
 
         agent.agent_url_image = Some(image);
-        ctx.db().agents().save(agent).await?;
+        save_agent_change(&mut ctx, &author, agent, None).await?;
 
         ctx.send(
             Response::new_user_reply(&author, "imagem alterada com sucesso! Se o URL não for um PNG válido, o agente vai dar erro sempre que for invocado. Tome cuidado!")
@@ -382,7 +469,7 @@ pub async fn configure_agent(
 
             user_data.remove_credits(config::PUBLISH_AGENT_PRICE);
             ctx.db().users().save(user_data).await?;
-            agent.is_waiting_for_approval = true;
+            agent.submit_for_approval();
             ctx.db().agents().save(agent.clone()).await?;
 
             ctx.client.emit_request_hook(agent).await?;
@@ -396,3 +483,186 @@ pub async fn configure_agent(
 
     Ok(())
 }
+
+/// Lists the agent's example dialogues and lets the creator add or remove one
+async fn configure_example_dialogues(
+    ctx: &mut CommandContext,
+    author: &User,
+    identifier: &str,
+) -> anyhow::Result<()> {
+    let Some(agent) = ctx.db().agents().get_by_identifier(identifier).await? else {
+        ctx.send(
+            Response::new_user_reply(author, "agente inválido ou inexistente")
+                .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let listing = if agent.example_dialogues.is_empty() {
+        "o agente ainda não tem exemplos de diálogo! Eles mostram ao cérebro como o agente fala, mantendo a personalidade dele desde a primeira mensagem.".to_string()
+    } else {
+        let examples = agent
+            .example_dialogues
+            .iter()
+            .enumerate()
+            .map(|(index, dialogue)| {
+                format!(
+                    "**{}.** 👤 `{}`\n💬 `{}`",
+                    index + 1,
+                    dialogue.user_message.chars().take(150).collect::<String>(),
+                    dialogue.agent_message.chars().take(150).collect::<String>()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("exemplos de diálogo de **{}**:\n{examples}", agent.name)
+    };
+
+    let mut buttons = vec![ButtonBuilder::new()
+        .set_custom_id("add")
+        .set_label("Adicionar exemplo")
+        .set_style(ButtonStyle::Secondary)
+        .set_disabled(agent.example_dialogues.len() >= config::MAX_EXAMPLE_DIALOGUES)];
+    if !agent.example_dialogues.is_empty() {
+        buttons.push(
+            ButtonBuilder::new()
+                .set_custom_id("remove")
+                .set_label("Remover exemplo")
+                .set_style(ButtonStyle::Secondary),
+        );
+    }
+
+    let Some(custom_id) = await_button_choice(
+        ctx,
+        author,
+        Response::new_user_reply(author, listing).add_emoji_prefix("💬"),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    if custom_id == "remove" {
+        let buttons = (1..=agent.example_dialogues.len())
+            .map(|number| {
+                ButtonBuilder::new()
+                    .set_custom_id(number.to_string())
+                    .set_label(number.to_string())
+                    .set_style(ButtonStyle::Secondary)
+            })
+            .collect::<Vec<_>>();
+
+        let Some(number) = await_button_choice(
+            ctx,
+            author,
+            Response::new_user_reply(author, "qual exemplo você quer remover?")
+                .add_emoji_prefix("🗑️"),
+            buttons,
+        )
+        .await?
+        else {
+            return Ok(());
+        };
+
+        let Some(mut agent) = ctx.db().agents().get_by_identifier(identifier).await? else {
+            return Ok(());
+        };
+
+        let index = number.parse::<usize>().unwrap_or(0).saturating_sub(1);
+        if index < agent.example_dialogues.len() {
+            agent.example_dialogues.remove(index);
+            save_agent_change(ctx, author, agent, None).await?;
+        }
+
+        ctx.send(
+            Response::new_user_reply(
+                author,
+                "exemplo removido com sucesso! A mudança vale para as próximas invocações.",
+            )
+            .add_emoji_prefix(emojis::SUCCESS),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(user_message) = get_input(
+        ctx,
+        author,
+        Response::new_user_reply(
+            author,
+            "escreva a mensagem que um usuário manda no exemplo:",
+        ),
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let Some(agent_message) = get_input(
+        ctx,
+        author,
+        Response::new_user_reply(
+            author,
+            format!("agora escreva como **{}** responderia:", agent.name),
+        ),
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    if !config::EXAMPLE_DIALOGUE_MESSAGE_SIZE.contains(&user_message.chars().count())
+        || !config::EXAMPLE_DIALOGUE_MESSAGE_SIZE.contains(&agent_message.chars().count())
+    {
+        ctx.send(
+            Response::new_user_reply(
+                author,
+                format!(
+                    "as mensagens do exemplo devem ter no máximo {} caracteres!",
+                    config::EXAMPLE_DIALOGUE_MESSAGE_SIZE.end()
+                ),
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(mut agent) = ctx.db().agents().get_by_identifier(identifier).await? else {
+        return Ok(());
+    };
+
+    if agent.example_dialogues.len() >= config::MAX_EXAMPLE_DIALOGUES {
+        ctx.send(
+            Response::new_user_reply(
+                author,
+                format!(
+                    "o agente já tem o máximo de {} exemplos de diálogo!",
+                    config::MAX_EXAMPLE_DIALOGUES
+                ),
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    agent.example_dialogues.push(AgentExampleDialogue {
+        user_message,
+        agent_message,
+    });
+    save_agent_change(ctx, author, agent, None).await?;
+
+    ctx.send(
+        Response::new_user_reply(
+            author,
+            "exemplo adicionado com sucesso! A mudança vale para as próximas invocações.",
+        )
+        .add_emoji_prefix(emojis::SUCCESS),
+    )
+    .await?;
+
+    Ok(())
+}
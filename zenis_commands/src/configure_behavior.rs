@@ -0,0 +1,167 @@
+use std::time::Duration;
+
+use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
+use zenis_framework::{util::make_multiple_rows, watcher::WatcherOptions};
+
+use crate::{
+    invoke::{build_instance_settings, format_instance_settings},
+    prelude::*,
+};
+
+#[command("Configure o comportamento de um agente que você invocou neste chat!")]
+#[name("configurar comportamento")]
+pub async fn configure_behavior(
+    mut ctx: CommandContext,
+    #[rename("apenas_menção")]
+    #[description("Responder apenas quando for mencionado ou chamado pelo nome")]
+    mention_only: Option<bool>,
+    #[rename("chance_de_resposta")]
+    #[description("Chance (0-100%) de o agente considerar responder uma mensagem")]
+    reply_probability: Option<i64>,
+    #[rename("chance_de_resposta_a_agentes")]
+    #[description("Chance (0-100%) de o agente responder mensagens de outros agentes e bots")]
+    bot_reply_probability: Option<i64>,
+    #[rename("atraso_mínimo")]
+    #[description("Tempo mínimo (em segundos) entre as respostas do agente")]
+    min_delay_secs: Option<i64>,
+    #[rename("respostas_por_minuto")]
+    #[description("Máximo de respostas do agente por minuto (0 para remover o limite)")]
+    max_replies_per_minute: Option<i64>,
+    #[rename("remover_ações")]
+    #[description("Remover ações entre asteriscos (como *sorri*) das mensagens do agente")]
+    strip_italic_actions: Option<bool>,
+) -> anyhow::Result<()> {
+    let author = ctx.author().await?;
+    let channel_id = ctx
+        .interaction
+        .channel
+        .clone()
+        .context("Expected a channel")?
+        .id;
+
+    let instances = ctx
+        .db()
+        .instances()
+        .all_actives_in_channel(channel_id.get())
+        .await?
+        .into_iter()
+        .filter(|instance| instance.summoner_id == author.id.get())
+        .collect::<Vec<_>>();
+
+    if instances.is_empty() {
+        ctx.send(
+            Response::new_user_reply(&author, "você não invocou nenhum agente neste chat!")
+                .add_emoji_prefix(emojis::ERROR)
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let instance = if instances.len() == 1 {
+        instances[0].clone()
+    } else {
+        let buttons = instances
+            .iter()
+            .map(|instance| {
+                ButtonBuilder::new()
+                    .set_custom_id(instance.id.to_hex())
+                    .set_label(&instance.agent_name)
+                    .set_style(ButtonStyle::Secondary)
+            })
+            .collect::<Vec<_>>();
+
+        let message = ctx
+            .send(
+                Response::new_user_reply(&author, "escolha um agente para configurar:")
+                    .add_emoji_prefix("⚙️")
+                    .set_components(make_multiple_rows(buttons.clone())),
+            )
+            .await?;
+
+        let Ok(Some(interaction)) = ctx
+            .watcher
+            .await_single_component(
+                message.id,
+                move |interaction| interaction.author_id() == Some(author.id),
+                WatcherOptions {
+                    timeout: Duration::from_secs(60),
+                },
+            )
+            .await
+        else {
+            return Ok(());
+        };
+
+        let data = interaction.parse_message_component_data()?;
+
+        let buttons = buttons
+            .iter()
+            .map(|b| {
+                let id = b.data.custom_id.as_ref();
+                b.clone()
+                    .set_disabled(true)
+                    .set_style(if id == Some(&data.custom_id) {
+                        ButtonStyle::Success
+                    } else {
+                        ButtonStyle::Secondary
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        ctx = CommandContext::from_with_interaction(&ctx, Box::new(interaction));
+        ctx.update_message(Response::default().set_components(make_multiple_rows(buttons)))
+            .await?;
+
+        let Some(instance) = instances
+            .iter()
+            .find(|instance| instance.id.to_hex() == data.custom_id)
+        else {
+            return Ok(());
+        };
+
+        instance.clone()
+    };
+
+    let Some(mut instance) = ctx.db().instances().get_by_id(instance.id).await? else {
+        ctx.send(
+            Response::new_user_reply(&author, "esse agente não foi encontrado!")
+                .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    instance.settings = build_instance_settings(
+        instance.settings,
+        mention_only,
+        reply_probability,
+        min_delay_secs,
+        max_replies_per_minute,
+    );
+    if let Some(bot_reply_probability) = bot_reply_probability {
+        instance.settings.bot_reply_probability = bot_reply_probability.clamp(0, 100) as u8;
+    }
+    if let Some(strip_italic_actions) = strip_italic_actions {
+        instance.settings.strip_italic_actions = strip_italic_actions;
+    }
+
+    let embed = EmbedBuilder::new_common()
+        .set_color(Color::GREEN)
+        .set_author(EmbedAuthor {
+            name: format!("Comportamento de {}", instance.agent_name),
+            icon_url: Some(author.avatar_url()),
+        })
+        .set_description(format_instance_settings(&instance.settings));
+
+    ctx.db().instances().save(instance).await?;
+
+    ctx.send(
+        Response::new_user_reply(&author, "comportamento do agente atualizado!")
+            .add_emoji_prefix(emojis::SUCCESS)
+            .add_embed(embed),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use zenis_database::earning_model::EarningSource;
+use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
+
+use crate::{
+    invoke::{
+        ask_for_payment_method, charge_invocation, check_summon_permissions, make_system_prompt,
+        refund_invocation,
+    },
+    prelude::*,
+};
+
+#[command("Continue uma sessão salva com um agente, neste chat!")]
+#[name("continuar")]
+pub async fn continue_session(
+    mut ctx: CommandContext,
+    #[rename("sessão")]
+    #[description("O nome da sessão que você quer continuar")]
+    #[autocomplete(suggest_session_names)]
+    session_name: Option<String>,
+) -> anyhow::Result<()> {
+    let author = ctx.author().await?;
+    let author_id = author.id;
+
+    let Some(channel) = ctx.interaction.channel.clone() else {
+        return Ok(());
+    };
+
+    let Some(guild_id) = ctx.interaction.guild_id else {
+        ctx.reply(
+            Response::new_user_reply(
+                &author,
+                "você precisa estar em um servidor para usar esse comando!",
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+    if !check_summon_permissions(
+        &mut ctx,
+        &author,
+        &guild_data.agent_permissions,
+        (channel.id.get(), channel.parent_id.map(|id| id.get())),
+    )
+    .await?
+    {
+        return Ok(());
+    }
+
+    let mut sessions = ctx
+        .db()
+        .sessions()
+        .get_all_by_owner(author_id.get())
+        .await?;
+    if sessions.is_empty() {
+        ctx.reply(
+            Response::new_user_reply(
+                &author,
+                "você não tem nenhuma sessão salva! Sessões são salvas quando um agente que você invocou é desligado.",
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let session = match session_name {
+        Some(session_name) => {
+            let Some(index) = sessions
+                .iter()
+                .position(|s| clear_string(&s.name) == clear_string(session_name.trim()))
+            else {
+                ctx.reply(
+                    Response::new_user_reply(&author, "você não tem uma sessão com esse nome!")
+                        .add_emoji_prefix(emojis::ERROR),
+                )
+                .await?;
+                return Ok(());
+            };
+
+            sessions.remove(index)
+        }
+        None => {
+            let buttons = sessions
+                .iter()
+                .take(20)
+                .map(|session| {
+                    ButtonBuilder::new()
+                        .set_custom_id(session.id.to_hex())
+                        .set_label(session.name.chars().take(80).collect::<String>())
+                        .set_style(ButtonStyle::Secondary)
+                })
+                .collect::<Vec<_>>();
+
+            let Some(custom_id) = await_button_choice(
+                &mut ctx,
+                &author,
+                Response::new_user_reply(&author, "escolha uma sessão para continuar:")
+                    .add_emoji_prefix("📖"),
+                buttons,
+            )
+            .await?
+            else {
+                return Ok(());
+            };
+
+            let Some(index) = sessions.iter().position(|s| s.id.to_hex() == custom_id) else {
+                return Ok(());
+            };
+
+            sessions.remove(index)
+        }
+    };
+
+    let channel_instances = ctx
+        .db()
+        .instances()
+        .get_all_by_channel(channel.id.get())
+        .await?;
+
+    let guild_settings = guild_data.settings;
+    if channel_instances.len() >= guild_settings.max_agents_per_channel as usize {
+        ctx.send(
+            Response::new_user_reply(&author, "já há muitos agentes neste chat!")
+                .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if channel_instances
+        .iter()
+        .any(|instance| instance.agent_identifier == session.agent.identifier)
+    {
+        ctx.send(
+            Response::new_user_reply(&author, "esse agente já está invocado neste chat!")
+                .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // Prices follow the current agent, but the personality stays the one saved in the session
+    let snapshot = session.agent.to_agent_model();
+    let current_agent = ctx
+        .db()
+        .agents()
+        .get_by_identifier(&session.agent.identifier)
+        .await?;
+    let mut pricing = current_agent
+        .as_ref()
+        .map(|agent| agent.pricing)
+        .unwrap_or(snapshot.pricing);
+    pricing.price_per_reply += session.brain.extra_price_per_reply();
+
+    let Some(payment_method) = ask_for_payment_method(
+        &mut ctx,
+        &snapshot,
+        session.brain,
+        pricing,
+        author_id,
+        guild_id,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    if !charge_invocation(&mut ctx, &author, pricing, payment_method).await? {
+        return Ok(());
+    }
+
+    let guild = ctx.client.get_guild(guild_id).await?;
+
+    let mut embed = EmbedBuilder::new_common()
+        .set_color(Color::GREEN)
+        .set_author(EmbedAuthor {
+            name: format!("Sessão continuada por {}", author.display_name()),
+            icon_url: Some(author.avatar_url()),
+        })
+        .set_description(format!(
+            "## {} voltou!\nContinuando a sessão **{}**.",
+            session.agent.name, session.name
+        ))
+        .add_footer_text(format!("Cérebro: {}", session.brain.name()));
+
+    if !session.summary.is_empty() {
+        embed = embed.add_not_inlined_field(
+            "📜 Resumo",
+            session.summary.chars().take(1000).collect::<String>(),
+        );
+    }
+
+    if let Some(image_url) = &session.agent.agent_url_image {
+        embed = embed.set_thumbnail(image_url);
+    }
+
+    let message = ctx.send(embed).await?;
+    let system_prompt = make_system_prompt(
+        &snapshot,
+        &guild,
+        &guild_settings,
+        channel.nsfw.unwrap_or(false),
+    );
+
+    let agent_identifier = session.agent.identifier.clone();
+    let result = ctx
+        .client
+        .resume_agent_instance(
+            ctx.db(),
+            session,
+            (channel.id, author_id),
+            pricing,
+            payment_method,
+            system_prompt,
+        )
+        .await;
+
+    if let Some(e) = result.err() {
+        ctx.client
+            .http
+            .delete_message(message.channel_id, message.id)
+            .await?;
+
+        ctx.client
+            .emit_error_hook(
+                format!("Session resume failed. Agent ID: {}", agent_identifier),
+                e,
+            )
+            .await
+            .ok();
+
+        refund_invocation(&ctx, payment_method, pricing.price_per_invocation).await?;
+
+        ctx.send(
+            Response::new_user_reply(&author, "**algo deu errado ao continuar a sessão!**\nTalvez eu não tenha permissão de criar webhooks aqui. Você não foi cobrado.\nSe o erro persistir, entre em **/servidoroficial** e busque suporte!")
+                .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    ctx.db()
+        .earnings()
+        .credit_creators(
+            current_agent.as_ref().unwrap_or(&snapshot),
+            EarningSource::Invocation,
+            pricing.price_per_invocation,
+            payment_method,
+        )
+        .await?;
+
+    Ok(())
+}
@@ -1,16 +1,36 @@
 use std::time::Duration;
 
 use zenis_database::agent_model::{AgentModel, AgentPricing};
+use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
 use zenis_framework::watcher::WatcherOptions;
 
 use crate::prelude::*;
 
 #[command("Crie um agente personalizado!")]
 #[name("criar agente")]
-pub async fn create_agent(mut ctx: CommandContext) -> anyhow::Result<()> {
+pub async fn create_agent(
+    mut ctx: CommandContext,
+    #[rename("baseado_em")]
+    #[description("O ID de um agente público para criar uma variante dele")]
+    #[autocomplete(suggest_agent_ids)]
+    forked_from: Option<String>,
+) -> anyhow::Result<()> {
     let author = ctx.author().await?;
     let author_id = author.id;
 
+    if let Some(forked_from) = forked_from {
+        let Some(original) = ctx.db().agents().get_by_identifier(&forked_from).await? else {
+            ctx.send(
+                Response::new_user_reply(&author, "agente inválido ou inexistente")
+                    .add_emoji_prefix(emojis::ERROR),
+            )
+            .await?;
+            return Ok(());
+        };
+
+        return fork_agent(&mut ctx, &author, original).await;
+    }
+
     let channel = ctx
         .interaction
         .channel
@@ -175,7 +195,7 @@ pub async fn create_agent(mut ctx: CommandContext) -> anyhow::Result<()> {
     }
 
     // Invocation price
-    ctx.send(Response::new_user_reply(&author, "escreva o preço em créditos por invocar o seu agente (ex: 0, 3, 8): (você receberá 25% desse valor por cada invocação)")).await?;
+    ctx.send(Response::new_user_reply(&author, format!("escreva o preço em créditos por invocar o seu agente (ex: 0, 3, 8): (você receberá {}% desse valor por cada invocação e {}% do preço por resposta, veja em **/ganhos**)", *config::CREATOR_INVOCATION_REVENUE_SHARE_PERCENT, *config::CREATOR_REPLY_REVENUE_SHARE_PERCENT))).await?;
 
     let Ok(Some(message)) = ctx
         .watcher
@@ -200,13 +220,12 @@ pub async fn create_agent(mut ctx: CommandContext) -> anyhow::Result<()> {
         return Ok(());
     };
 
-    let agent_price = agent_price.clamp(0, 100) as i64;
-
-    let mut name_for_identifier = clear_string(&agent_name);
-    name_for_identifier = name_for_identifier.replace(' ', "_");
-    name_for_identifier = name_for_identifier.replace('/', "-");
+    let agent_price = (agent_price as i64).clamp(
+        *config::PRICE_PER_INVOCATION_RANGE.start(),
+        *config::PRICE_PER_INVOCATION_RANGE.end(),
+    );
 
-    let identifier = format!("{}/{}", clear_string(&author.name), name_for_identifier);
+    let identifier = make_agent_identifier(&author, &agent_name);
 
     if ctx
         .db()
@@ -312,3 +331,227 @@ pub async fn create_agent(mut ctx: CommandContext) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+pub(crate) fn make_agent_identifier(author: &User, agent_name: &str) -> String {
+    let mut name_for_identifier = clear_string(agent_name);
+    name_for_identifier = name_for_identifier.replace(' ', "_");
+    name_for_identifier = name_for_identifier.replace('/', "-");
+
+    format!("{}/{}", clear_string(&author.name), name_for_identifier)
+}
+
+/// Creates a private copy of `original` under the author, recording where it came from
+pub(crate) async fn fork_agent(
+    ctx: &mut CommandContext,
+    author: &User,
+    original: AgentModel,
+) -> anyhow::Result<()> {
+    let author_id = author.id;
+    let channel = ctx
+        .interaction
+        .channel
+        .clone()
+        .context("Expected a channel ID")?;
+    let is_own_agent = original.creator_user_id == author_id.get();
+
+    if !original.public && !is_own_agent {
+        ctx.send(
+            Response::new_user_reply(
+                author,
+                "você só pode criar variantes de agentes públicos ou dos seus próprios agentes!",
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let user_data = ctx.db().users().get_by_user(author_id).await?;
+    if user_data.credits < config::CREATE_AGENT_PRICE {
+        ctx.send(Response::new_user_reply(author, format!("você não tem suficientes créditos para criar essa variante! Criar agentes custa {} créditos.", config::CREATE_AGENT_PRICE)).add_emoji_prefix(emojis::ERROR)).await?;
+        return Ok(());
+    }
+
+    ctx.send(Response::new_user_reply(
+        author,
+        format!(
+            "escreva o nome da sua variante de **{}** (ou envie um **.** para manter o nome):",
+            original.name
+        ),
+    ))
+    .await?;
+
+    let Ok(Some(message)) = ctx
+        .watcher
+        .await_single_message(
+            channel.id,
+            move |message| message.author.id == author_id,
+            WatcherOptions {
+                timeout: Duration::from_secs(60),
+            },
+        )
+        .await
+    else {
+        return Ok(());
+    };
+
+    let mut agent_name = message.content.trim().replace('\n', " ");
+    if agent_name == "." {
+        agent_name = original.name.clone();
+    }
+
+    if !config::NAME_SIZE.contains(&agent_name.len()) {
+        ctx.send(
+            Response::new_user_reply(
+                author,
+                format!(
+                    "o nome do agente deve ter no máximo {} caracteres!",
+                    config::NAME_SIZE.end()
+                ),
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let identifier = make_agent_identifier(author, &agent_name);
+    if ctx
+        .db()
+        .agents()
+        .get_by_identifier(&identifier)
+        .await?
+        .is_some()
+    {
+        ctx.send(Response::new_user_reply(author, format!("o identificador gerado para a sua variante é **{identifier}**. Mas já existe um agente com esse identificador! Nomeie sua variante com outro nome.")).add_emoji_prefix(emojis::ERROR)).await?;
+        return Ok(());
+    }
+
+    let shares_revenue = if is_own_agent {
+        false
+    } else {
+        let buttons = vec![
+            ButtonBuilder::new()
+                .set_custom_id("share")
+                .set_label("Dividir ganhos")
+                .set_style(ButtonStyle::Secondary),
+            ButtonBuilder::new()
+                .set_custom_id("keep")
+                .set_label("Não dividir")
+                .set_style(ButtonStyle::Secondary),
+        ];
+
+        let Some(custom_id) = await_button_choice(
+            ctx,
+            author,
+            Response::new_user_reply(
+                author,
+                format!(
+                    "você quer dar **{}%** dos ganhos da sua variante para o criador de **{}**?",
+                    *config::FORK_REVENUE_SHARE_PERCENT,
+                    original.name
+                ),
+            )
+            .add_emoji_prefix("🍴"),
+            buttons,
+        )
+        .await?
+        else {
+            return Ok(());
+        };
+
+        custom_id == "share"
+    };
+
+    let mut agent_model = original.fork(author_id.get(), &identifier, &agent_name, shares_revenue);
+    if let Some(guild_id) = channel.guild_id {
+        agent_model = agent_model.with_guild_id(guild_id.get());
+    }
+
+    let display_description = agent_model.description.chars().take(60).collect::<String>();
+
+    let mut confirmation_embed = EmbedBuilder::new_common()
+        .set_color(Color::YELLOW)
+        .set_author(EmbedAuthor {
+            name: format!("Criação da variante {}", agent_name),
+            icon_url: Some(author.avatar_url()),
+        })
+        .add_inlined_field("📄 Nome", format!("**{}**", agent_name))
+        .add_inlined_field("😀 Descrição", display_description)
+        .add_inlined_field(
+            "🍴 Baseado em",
+            format!("**{}** (`{}`)", original.name, original.identifier),
+        )
+        .add_inlined_field(
+            "💸 Divisão de ganhos",
+            if shares_revenue {
+                format!(
+                    "{}% para o criador original",
+                    *config::FORK_REVENUE_SHARE_PERCENT
+                )
+            } else {
+                "Não".to_string()
+            },
+        )
+        .add_footer_text(format!("ID do agente: {}", identifier));
+
+    if let Some(image_url) = &agent_model.agent_url_image {
+        confirmation_embed = confirmation_embed.set_thumbnail(image_url);
+    }
+
+    let confirmation = ctx
+        .helper()
+        .create_confirmation(
+            author_id,
+            false,
+            Response::new_user_reply(
+                author,
+                format!(
+                    "você quer mesmo criar essa variante? **Criar um agente custa {}₢**.",
+                    config::CREATE_AGENT_PRICE
+                ),
+            )
+            .add_embed(confirmation_embed),
+        )
+        .await?;
+    if !confirmation {
+        return Ok(());
+    }
+
+    let mut user_data = ctx.db().users().get_by_user(author_id).await?;
+    if user_data.credits < config::CREATE_AGENT_PRICE {
+        ctx.send(
+            Response::new_user_reply(
+                author,
+                "você não tem suficientes créditos para criar essa variante!",
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    user_data.remove_credits(config::CREATE_AGENT_PRICE);
+    ctx.db().users().save(user_data).await?;
+
+    ctx.db().agents().create_agent(agent_model).await?;
+
+    if let Some(mut original) = ctx.db().agents().get_by_id(original.id).await? {
+        original.stats.forks += 1;
+        ctx.db().agents().save(original).await?;
+    }
+
+    ctx.send(
+        Response::new_user_reply(
+            author,
+            format!(
+                "a variante **{}** foi criada com sucesso! Ao invocar ela, use o ID: `{identifier}`\n-> Use **/configurar agente** para mudar a personalidade da sua variante.",
+                agent_name
+            ),
+        )
+        .add_emoji_prefix(emojis::SUCCESS),
+    )
+    .await?;
+
+    Ok(())
+}
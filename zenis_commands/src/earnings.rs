@@ -0,0 +1,127 @@
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use zenis_database::earning_model::EarningSource;
+
+use crate::prelude::*;
+
+/// Agents listed per day, so the field stays within Discord's limits
+const MAX_AGENTS_PER_DAY: usize = 5;
+
+#[derive(Debug, Default)]
+struct AgentEarnings {
+    name: String,
+    invocations: i64,
+    replies: i64,
+}
+
+impl AgentEarnings {
+    fn total(&self) -> i64 {
+        self.invocations + self.replies
+    }
+}
+
+#[command("Veja quantos créditos os seus agentes renderam nos últimos dias!")]
+#[name("ganhos")]
+pub async fn earnings(mut ctx: CommandContext) -> anyhow::Result<()> {
+    let author = ctx.author().await?;
+
+    let since = Utc::now().timestamp() - config::EARNINGS_DASHBOARD_DAYS * 24 * 60 * 60;
+    let earnings = ctx
+        .db()
+        .earnings()
+        .get_all_by_creator_since(author.id.get(), since)
+        .await?;
+
+    if earnings.is_empty() {
+        ctx.reply(
+            Response::new_user_reply(
+                &author,
+                format!(
+                    "seus agentes não renderam créditos nos últimos {} dias! Publique seus agentes com **/configurar agente** para ganhar parte do que os usuários pagam por eles.",
+                    config::EARNINGS_DASHBOARD_DAYS
+                ),
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // Grouped by UTC day (most recent first), then by agent. Shares of forks are kept apart from own agents
+    let mut days: BTreeMap<Reverse<NaiveDate>, BTreeMap<(String, bool), AgentEarnings>> =
+        BTreeMap::new();
+    for earning in earnings.iter() {
+        let Some(date) = DateTime::from_timestamp(earning.created_at_timestamp, 0) else {
+            continue;
+        };
+
+        let agent_earnings = days
+            .entry(Reverse(date.date_naive()))
+            .or_default()
+            .entry((earning.agent_identifier.clone(), earning.is_fork_share))
+            .or_default();
+        agent_earnings.name = earning.agent_name.clone();
+        match earning.source {
+            EarningSource::Invocation => agent_earnings.invocations += earning.amount,
+            EarningSource::Reply => agent_earnings.replies += earning.amount,
+        }
+    }
+
+    let total = earnings.iter().map(|earning| earning.amount).sum::<i64>();
+    let mut embed = EmbedBuilder::new_common()
+        .set_color(Color::YELLOW)
+        .set_author(EmbedAuthor {
+            name: format!("Ganhos de {}", author.display_name()),
+            icon_url: Some(author.avatar_url()),
+        })
+        .set_description(format!(
+            "{} **Total nos últimos {} dias**: `{}₢`",
+            emojis::CREDIT,
+            config::EARNINGS_DASHBOARD_DAYS,
+            total
+        ))
+        .add_footer_text(format!(
+            "Você recebe {}% do preço de invocação e {}% do preço por resposta dos seus agentes.\n🍴 = sua parte dos ganhos de variantes dos seus agentes.",
+            *config::CREATOR_INVOCATION_REVENUE_SHARE_PERCENT,
+            *config::CREATOR_REPLY_REVENUE_SHARE_PERCENT
+        ));
+
+    for (Reverse(date), agents) in days {
+        let day_total = agents.values().map(AgentEarnings::total).sum::<i64>();
+
+        let mut agents = agents.into_iter().collect::<Vec<_>>();
+        agents.sort_by_key(|(_, agent_earnings)| Reverse(agent_earnings.total()));
+
+        let mut lines = agents
+            .iter()
+            .take(MAX_AGENTS_PER_DAY)
+            .map(|((identifier, is_fork_share), agent_earnings)| {
+                format!(
+                    "{}**{}** (`{}`): `{}₢` (invocações: `{}₢`, respostas: `{}₢`)",
+                    if *is_fork_share { "🍴 " } else { "" },
+                    agent_earnings.name,
+                    identifier,
+                    agent_earnings.total(),
+                    agent_earnings.invocations,
+                    agent_earnings.replies
+                )
+            })
+            .collect::<Vec<_>>();
+        if agents.len() > MAX_AGENTS_PER_DAY {
+            lines.push(format!(
+                "*...e mais {} agentes*",
+                agents.len() - MAX_AGENTS_PER_DAY
+            ));
+        }
+
+        embed = embed.add_not_inlined_field(
+            format!("📅 {} — {}₢", date.format("%d/%m/%Y"), day_total),
+            lines.join("\n"),
+        );
+    }
+
+    ctx.reply(embed).await?;
+
+    Ok(())
+}
@@ -4,17 +4,58 @@ use zenis_database::agent_model::AgentModel;
 use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
 use zenis_framework::{util::make_multiple_rows, watcher::WatcherOptions};
 
-use crate::prelude::*;
+use crate::{create_agent::fork_agent, prelude::*};
 
 const AGENTS_PER_PAGE: usize = 4;
+const MAX_SEARCH_RESULTS: usize = 40;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum AgentSort {
+    Rating,
+    Popularity,
+    Recency,
+}
 
 #[command("Veja todos os agentes que você pode invocar!")]
 #[name("explorar")]
-pub async fn explore(mut ctx: CommandContext) -> anyhow::Result<()> {
+pub async fn explore(
+    mut ctx: CommandContext,
+    #[rename("busca")]
+    #[description("Procure agentes pelo nome, descrição ou tags")]
+    query: Option<String>,
+) -> anyhow::Result<()> {
     let author = ctx.author().await?;
     let guild_id = ctx.interaction.guild_id;
     let author_id = author.id;
 
+    if let Some(query) = query {
+        let agents = ctx
+            .db()
+            .agents()
+            .search(
+                &query,
+                author_id.get(),
+                guild_id.map(|id| id.get()),
+                MAX_SEARCH_RESULTS,
+            )
+            .await?;
+
+        if agents.is_empty() {
+            ctx.reply(
+                Response::new_user_reply(
+                    &author,
+                    format!("nenhum agente foi encontrado para **{}**!", query.trim()),
+                )
+                .add_emoji_prefix(emojis::ERROR),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        // Search results are already ordered by relevance
+        return generate_pagination(&mut ctx, &author, agents, None).await;
+    }
+
     let buttons = vec![
         ButtonBuilder::new()
             .set_custom_id("public")
@@ -71,9 +112,9 @@ pub async fn explore(mut ctx: CommandContext) -> anyhow::Result<()> {
         .await?;
 
     let db = ctx.db();
-    if data.custom_id == "public" {
-        generate_pagination(&mut ctx, &author, db.agents().get_all_public().await?).await?;
-    } else if data.custom_id == "private" {
+    let agents = if data.custom_id == "public" {
+        db.agents().get_all_public().await?
+    } else {
         let mut private = ctx
             .db()
             .agents()
@@ -91,17 +132,89 @@ pub async fn explore(mut ctx: CommandContext) -> anyhow::Result<()> {
         };
 
         private.append(&mut guild_private_agents);
+        private
+    };
+
+    let sort = if agents.len() > 1 {
+        let Some(sort) = ask_for_sort(&mut ctx, &author).await? else {
+            return Ok(());
+        };
 
-        generate_pagination(&mut ctx, &author, private).await?;
+        sort
+    } else {
+        AgentSort::Popularity
+    };
+
+    generate_pagination(&mut ctx, &author, agents, Some(sort)).await
+}
+
+async fn ask_for_sort(
+    ctx: &mut CommandContext,
+    author: &User,
+) -> anyhow::Result<Option<AgentSort>> {
+    let buttons = vec![
+        ButtonBuilder::new()
+            .set_custom_id("popularity")
+            .set_label("Mais populares")
+            .set_style(ButtonStyle::Secondary),
+        ButtonBuilder::new()
+            .set_custom_id("rating")
+            .set_label("Melhor avaliados")
+            .set_style(ButtonStyle::Secondary),
+        ButtonBuilder::new()
+            .set_custom_id("recency")
+            .set_label("Mais recentes")
+            .set_style(ButtonStyle::Secondary),
+    ];
+
+    let Some(custom_id) = await_button_choice(
+        ctx,
+        author,
+        Response::new_user_reply(author, "como você quer ordenar os agentes?")
+            .add_emoji_prefix("🔃"),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(match custom_id.as_str() {
+        "rating" => AgentSort::Rating,
+        "recency" => AgentSort::Recency,
+        _ => AgentSort::Popularity,
+    }))
+}
+
+fn sort_agents(agents: &mut [AgentModel], sort: AgentSort) {
+    match sort {
+        AgentSort::Popularity => agents.sort_by(|a, b| {
+            (b.stats.invocations, b.stats.replies).cmp(&(a.stats.invocations, a.stats.replies))
+        }),
+        // Agents without ratings go last
+        AgentSort::Rating => agents.sort_by(|a, b| {
+            let a_rating = a.stats.average_rating().unwrap_or(-1.0);
+            let b_rating = b.stats.average_rating().unwrap_or(-1.0);
+            b_rating
+                .total_cmp(&a_rating)
+                .then(b.stats.rating_count.cmp(&a.stats.rating_count))
+        }),
+        AgentSort::Recency => agents.sort_by_key(|agent| std::cmp::Reverse(agent.id.timestamp())),
     }
+}
 
-    Ok(())
+fn format_rating(agent: &AgentModel) -> String {
+    match agent.stats.average_rating() {
+        Some(rating) => format!("{:.1} ★ ({})", rating, agent.stats.rating_count),
+        None => "Sem avaliações".to_string(),
+    }
 }
 
 async fn generate_pagination(
     ctx: &mut CommandContext,
     author: &User,
     mut agents: Vec<AgentModel>,
+    sort: Option<AgentSort>,
 ) -> anyhow::Result<()> {
     if agents.is_empty() {
         ctx.reply(
@@ -115,7 +228,9 @@ async fn generate_pagination(
         return Ok(());
     }
 
-    agents.sort_unstable_by_key(|agent| u64::MAX.saturating_sub(agent.stats.invocations));
+    if let Some(sort) = sort {
+        sort_agents(&mut agents, sort);
+    }
 
     // Remove duplicated agent's with repeated identifiers
     let mut unique_agents = Vec::with_capacity(agents.len());
@@ -130,6 +245,17 @@ async fn generate_pagination(
 
     agents = unique_agents;
 
+    let top_reviews = ctx
+        .db()
+        .reviews()
+        .get_top_commented_by_agents(
+            &agents
+                .iter()
+                .map(|agent| agent.identifier.clone())
+                .collect::<Vec<_>>(),
+        )
+        .await?;
+
     let mut pages = vec![];
     for i in (0..agents.len()).step_by(AGENTS_PER_PAGE) {
         let mut page = EmbedBuilder::new_common()
@@ -147,10 +273,36 @@ async fn generate_pagination(
             let mut display_description = agent.description.clone();
             display_description.truncate(80);
 
+            let lineage = match &agent.forked_from {
+                Some(lineage) => format!(
+                    "\n**Baseado em**: {} (`{}`)",
+                    lineage.name, lineage.identifier
+                ),
+                None => String::new(),
+            };
+
+            let top_review = match top_reviews
+                .iter()
+                .find(|review| review.agent_identifier == agent.identifier)
+            {
+                Some(review) => format!(
+                    "\n> {} {}",
+                    review.stars(),
+                    review
+                        .comment
+                        .as_deref()
+                        .unwrap_or_default()
+                        .chars()
+                        .take(120)
+                        .collect::<String>()
+                ),
+                None => String::new(),
+            };
+
             page = page.add_not_inlined_field(
                 &agent.name,
                 format!(
-                    "**ID**: `{}`\n**Descrição**: `{}...`\n**Preço por invocação**: `{}₢`\n**Invocações totais**: `{}`",
+                    "**ID**: `{}`\n**Descrição**: `{}...`\n**Preço por invocação**: `{}₢`\n**Preço por resposta**: {}\n**Invocações totais**: `{}`\n**Avaliação**: `{}`\n**Variantes**: `{}`{}{}",
                     agent.identifier,
                     display_description,
                     if agent.pricing.price_per_invocation > 0 {
@@ -158,7 +310,12 @@ async fn generate_pagination(
                     } else {
                         "GRÁTIS ".to_string()
                     },
-                    agent.stats.invocations
+                    format_reply_prices(&agent.pricing),
+                    agent.stats.invocations,
+                    format_rating(agent),
+                    agent.stats.forks,
+                    lineage,
+                    top_review
                 ),
             );
 
@@ -179,7 +336,56 @@ async fn generate_pagination(
         return Ok(());
     }
 
-    EmbedPagination::new(ctx.clone(), pages).send().await?;
+    let Some(action) = EmbedPagination::new(ctx.clone(), pages)
+        .add_extra_button(
+            ButtonBuilder::new()
+                .set_custom_id("fork")
+                .set_label("Criar variante")
+                .set_emoji(Emoji::from_unicode("🍴"))
+                .set_style(ButtonStyle::Secondary),
+        )
+        .send()
+        .await?
+    else {
+        return Ok(());
+    };
+
+    // The agents shown in the page where the fork button was clicked
+    let page_agents = agents
+        .into_iter()
+        .skip(action.page * AGENTS_PER_PAGE)
+        .take(AGENTS_PER_PAGE)
+        .collect::<Vec<_>>();
+
+    let buttons = page_agents
+        .iter()
+        .map(|agent| {
+            ButtonBuilder::new()
+                .set_custom_id(&agent.identifier)
+                .set_label(&agent.name)
+                .set_style(ButtonStyle::Secondary)
+        })
+        .collect::<Vec<_>>();
+
+    let mut ctx = action.ctx;
+    let Some(identifier) = await_button_choice(
+        &mut ctx,
+        author,
+        Response::new_user_reply(author, "escolha o agente que você quer usar como base:")
+            .add_emoji_prefix("🍴"),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let Some(original) = page_agents
+        .into_iter()
+        .find(|agent| agent.identifier == identifier)
+    else {
+        return Ok(());
+    };
 
-    Ok(())
+    fork_agent(&mut ctx, author, original).await
 }
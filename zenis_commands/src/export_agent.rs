@@ -0,0 +1,91 @@
+use zenis_ai::character_card::CharacterCard;
+use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
+
+use crate::prelude::*;
+
+#[command("Exporte um dos seus agentes como Character Card (.json ou .png)!")]
+#[name("exportar agente")]
+pub async fn export_agent(
+    mut ctx: CommandContext,
+    #[rename("id")]
+    #[description("O ID do agente que você quer exportar")]
+    #[autocomplete(suggest_own_agent_ids)]
+    identifier: String,
+) -> anyhow::Result<()> {
+    let author = ctx.author().await?;
+
+    let Some(agent) = ctx.db().agents().get_by_identifier(&identifier).await? else {
+        ctx.send(
+            Response::new_user_reply(&author, "agente inválido ou inexistente")
+                .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    if agent.creator_user_id != author.id.get() {
+        ctx.send(
+            Response::new_user_reply(
+                &author,
+                "você não pode exportar esse agente pois não é seu!",
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let buttons = vec![
+        ButtonBuilder::new()
+            .set_custom_id("json")
+            .set_label("JSON")
+            .set_style(ButtonStyle::Secondary),
+        ButtonBuilder::new()
+            .set_custom_id("png")
+            .set_label("PNG")
+            .set_style(ButtonStyle::Secondary),
+    ];
+
+    let Some(custom_id) = await_button_choice(
+        &mut ctx,
+        &author,
+        Response::new_user_reply(&author, "escolha o formato do Character Card:")
+            .add_emoji_prefix("📁"),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let card = CharacterCard::from_agent(&agent);
+    let file_stem = clear_string(&agent.name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>();
+
+    let attachment = if custom_id == "png" {
+        // A broken agent image shouldn't stop the export, the card just gets a blank one
+        let png = match card.to_png(agent.agent_url_image.as_deref()).await {
+            Ok(png) => png,
+            Err(_) => card.to_png(None).await?,
+        };
+
+        DiscordAttachment::from_bytes(format!("{file_stem}.png"), png, 0)
+    } else {
+        DiscordAttachment::from_bytes(format!("{file_stem}.json"), card.to_json().into_bytes(), 0)
+    };
+
+    ctx.reply(
+        Response::new_user_reply(
+            &author,
+            format!("aqui está o Character Card de **{}**!", agent.name),
+        )
+        .add_emoji_prefix(emojis::SUCCESS)
+        .set_attachments(vec![attachment])
+        .set_ephemeral(),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use zenis_ai::transcript::{Transcript, TranscriptFormat};
+use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
+
+use crate::prelude::*;
+
+#[command("Exporte a conversa atual ou uma conversa recente com um agente neste chat!")]
+#[name("exportar conversa")]
+pub async fn export_conversation(mut ctx: CommandContext) -> anyhow::Result<()> {
+    let author = ctx.author().await?;
+    let channel_id = ctx
+        .interaction
+        .channel
+        .clone()
+        .context("Expected a channel")?
+        .id;
+
+    let mut conversations = vec![];
+    for instance in ctx
+        .db()
+        .instances()
+        .get_all_by_channel(channel_id.get())
+        .await?
+    {
+        conversations.push((
+            instance.id.to_hex(),
+            instance.agent_name.clone(),
+            Transcript::from_history(&instance.agent_name, &instance.history),
+        ));
+    }
+
+    for archive in ctx
+        .db()
+        .archived_instances()
+        .get_recent_by_channel(channel_id.get(), 5)
+        .await?
+    {
+        conversations.push((
+            archive.id.to_hex(),
+            format!("{} (encerrada)", archive.agent_name),
+            Transcript::from_history(&archive.agent_name, &archive.history),
+        ));
+    }
+
+    conversations.retain(|(_, _, transcript)| !transcript.entries.is_empty());
+
+    if conversations.is_empty() {
+        ctx.reply(
+            Response::new_user_reply(
+                &author,
+                "não há nenhuma conversa recente com agentes neste chat!",
+            )
+            .add_emoji_prefix(emojis::ERROR)
+            .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let transcript = if conversations.len() == 1 {
+        conversations.remove(0).2
+    } else {
+        let buttons = conversations
+            .iter()
+            .map(|(id, label, _)| {
+                ButtonBuilder::new()
+                    .set_custom_id(id)
+                    .set_label(label)
+                    .set_style(ButtonStyle::Secondary)
+            })
+            .collect::<Vec<_>>();
+
+        let Some(custom_id) = await_button_choice(
+            &mut ctx,
+            &author,
+            Response::new_user_reply(&author, "escolha a conversa que você quer exportar:")
+                .add_emoji_prefix("📜"),
+            buttons,
+        )
+        .await?
+        else {
+            return Ok(());
+        };
+
+        let Some(index) = conversations.iter().position(|(id, _, _)| *id == custom_id) else {
+            return Ok(());
+        };
+
+        conversations.remove(index).2
+    };
+
+    let buttons = vec![
+        ButtonBuilder::new()
+            .set_custom_id("json")
+            .set_label("JSON")
+            .set_style(ButtonStyle::Secondary),
+        ButtonBuilder::new()
+            .set_custom_id("markdown")
+            .set_label("Markdown")
+            .set_style(ButtonStyle::Secondary),
+        ButtonBuilder::new()
+            .set_custom_id("html")
+            .set_label("HTML")
+            .set_style(ButtonStyle::Secondary),
+    ];
+
+    let Some(custom_id) = await_button_choice(
+        &mut ctx,
+        &author,
+        Response::new_user_reply(&author, "escolha o formato do arquivo:").add_emoji_prefix("📁"),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let format = match custom_id.as_str() {
+        "json" => TranscriptFormat::Json,
+        "markdown" => TranscriptFormat::Markdown,
+        _ => TranscriptFormat::Html,
+    };
+
+    let file_name = format!(
+        "conversa-{}.{}",
+        clear_string(&transcript.agent_name)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect::<String>(),
+        format.extension()
+    );
+
+    ctx.reply(
+        Response::new_user_reply(
+            &author,
+            format!("aqui está a conversa com **{}**!", transcript.agent_name),
+        )
+        .add_emoji_prefix(emojis::SUCCESS)
+        .set_attachments(vec![DiscordAttachment::from_bytes(
+            file_name,
+            transcript.render(format).into_bytes(),
+            0,
+        )])
+        .set_ephemeral(),
+    )
+    .await?;
+
+    Ok(())
+}
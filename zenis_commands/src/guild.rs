@@ -1,7 +1,8 @@
-use std::time::Duration;
-
+use zenis_database::{
+    guild_model::{GuildLanguage, NsfwPolicy},
+    instance_model::InstanceBrain,
+};
 use zenis_discord::twilight_model::{channel::message::component::ButtonStyle, guild::Permissions};
-use zenis_framework::{util::make_multiple_rows, watcher::WatcherOptions};
 
 use crate::prelude::*;
 
@@ -96,60 +97,48 @@ async fn admin_dashboard(
         ButtonBuilder::new()
             .set_custom_id("realoc_credits")
             .set_label("Realocar Créditos"),
+        ButtonBuilder::new()
+            .set_custom_id("output_safety")
+            .set_label("Segurança das Mensagens"),
+        ButtonBuilder::new()
+            .set_custom_id("flood_guard")
+            .set_label("Proteção contra Flood"),
+        ButtonBuilder::new()
+            .set_custom_id("settings")
+            .set_label("Configurações"),
+        ButtonBuilder::new()
+            .set_custom_id("agent_permissions")
+            .set_label("Permissões"),
     ];
 
-    let message = ctx
-        .followup_interaction(
-            Response::new_user_reply(
-                author,
-                "**bem vindo ao dashboard do servidor, administrador!**\nPor favor, escolha uma opção, caso você queira alterar algo no servidor:",
-            )
-            .add_emoji_prefix("🤫")
-            .set_ephemeral()
-            .set_components(make_multiple_rows(buttons.clone())),
+    let Some(custom_id) = await_button_choice(
+        ctx,
+        author,
+        Response::new_user_reply(
+            author,
+            "**bem vindo ao dashboard do servidor, administrador!**\nPor favor, escolha uma opção, caso você queira alterar algo no servidor:",
         )
-        .await?.model().await?;
-
-    let author_id = author.id;
-    let Ok(Some(interaction)) = ctx
-        .watcher
-        .await_single_component(
-            message.id,
-            move |interaction| interaction.author_id() == Some(author_id),
-            WatcherOptions {
-                timeout: Duration::from_secs(30),
-            },
-        )
-        .await
+        .add_emoji_prefix("🤫"),
+        buttons,
+    )
+    .await?
     else {
         return Ok(());
     };
 
-    let data = interaction.parse_message_component_data()?;
-
-    let buttons = buttons
-        .iter()
-        .map(|b| {
-            let id = b.data.custom_id.as_ref();
-            b.clone()
-                .set_disabled(true)
-                .set_style(if id == Some(&data.custom_id) {
-                    ButtonStyle::Success
-                } else {
-                    ButtonStyle::Secondary
-                })
-        })
-        .collect::<Vec<_>>();
-
-    let mut ctx = CommandContext::from_with_interaction(ctx, Box::new(interaction));
-    ctx.update_message(Response::default().set_components(make_multiple_rows(buttons)))
-        .await?;
-
-    if data.custom_id == "cancel" {
+    if custom_id == "cancel" {
         return Ok(());
-    } else if data.custom_id == "realoc_credits" {
+    } else if custom_id == "output_safety" {
+        output_safety_dashboard(ctx, author, guild_id).await?;
+    } else if custom_id == "flood_guard" {
+        flood_guard_dashboard(ctx, author, guild_id).await?;
+    } else if custom_id == "settings" {
+        settings_dashboard(ctx, author, guild_id).await?;
+    } else if custom_id == "agent_permissions" {
+        agent_permissions_dashboard(ctx, author, guild_id).await?;
+    } else if custom_id == "realoc_credits" {
         let Ok(Some(public_credits)) = get_input(
-            &mut ctx, author,
+            ctx, author,
             Response::new_user_reply(author,
                 "quantos créditos PÚBLICOS você quer? Os créditos que restarem ficarão privados. Se não quiser nenhum crédito público, envie zero:"
             ).add_emoji_prefix(emojis::CREDIT)
@@ -209,3 +198,707 @@ async fn admin_dashboard(
 
     Ok(())
 }
+
+async fn output_safety_dashboard(
+    ctx: &mut CommandContext,
+    author: &User,
+    guild_id: Id<GuildMarker>,
+) -> anyhow::Result<()> {
+    let guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+    let settings = &guild_data.output_safety;
+
+    let yes_or_no = |value: bool| if value { "sim" } else { "não" };
+    let description = format!(
+        "Menções a @everyone, cargos e usuários que não participam da conversa nunca notificam ninguém.\n\n**Bloquear convites:** `{}`\n**Bloquear links:** `{}`\n**Domínios permitidos:** {}",
+        yes_or_no(settings.block_invites),
+        yes_or_no(settings.block_links),
+        if settings.allowed_domains.is_empty() {
+            "`nenhum`".to_string()
+        } else {
+            settings
+                .allowed_domains
+                .iter()
+                .map(|domain| format!("`{domain}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    );
+
+    let buttons = vec![
+        ButtonBuilder::new()
+            .set_custom_id("cancel")
+            .set_label("Cancelar")
+            .set_style(ButtonStyle::Danger),
+        ButtonBuilder::new()
+            .set_custom_id("toggle_invites")
+            .set_label(if settings.block_invites {
+                "Permitir Convites"
+            } else {
+                "Bloquear Convites"
+            }),
+        ButtonBuilder::new()
+            .set_custom_id("toggle_links")
+            .set_label(if settings.block_links {
+                "Permitir Links"
+            } else {
+                "Bloquear Links"
+            }),
+        ButtonBuilder::new()
+            .set_custom_id("allowed_domains")
+            .set_label("Domínios Permitidos"),
+    ];
+
+    let Some(custom_id) = await_button_choice(
+        ctx,
+        author,
+        Response::from(
+            EmbedBuilder::new_common()
+                .set_color(Color::CYAN)
+                .set_author(EmbedAuthor {
+                    name: "Segurança das mensagens dos agentes".to_string(),
+                    icon_url: Some(author.avatar_url()),
+                })
+                .set_description(description),
+        ),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let mut guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+    match custom_id.as_str() {
+        "toggle_invites" => {
+            guild_data.output_safety.block_invites = !guild_data.output_safety.block_invites;
+        }
+        "toggle_links" => {
+            guild_data.output_safety.block_links = !guild_data.output_safety.block_links;
+        }
+        "allowed_domains" => {
+            let Ok(Some(domains)) = get_input(
+                ctx,
+                author,
+                Response::new_user_reply(
+                    author,
+                    "envie os domínios permitidos separados por vírgula (exemplo: `youtube.com, wikipedia.org`). Envie `-` para não permitir nenhum:",
+                )
+                .add_emoji_prefix("🔗"),
+            )
+            .await
+            else {
+                return Ok(());
+            };
+
+            guild_data.output_safety.allowed_domains = domains
+                .split(',')
+                .map(|domain| {
+                    domain
+                        .trim()
+                        .trim_start_matches("https://")
+                        .trim_start_matches("http://")
+                        .trim_end_matches('/')
+                        .to_lowercase()
+                })
+                .filter(|domain| !domain.is_empty() && domain != "-")
+                .take(20)
+                .collect();
+        }
+        _ => return Ok(()),
+    }
+
+    ctx.db().guilds().save(guild_data).await?;
+    ctx.send(
+        Response::new_user_reply(author, "configurações de segurança atualizadas!")
+            .add_emoji_prefix(emojis::SUCCESS),
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn flood_guard_dashboard(
+    ctx: &mut CommandContext,
+    author: &User,
+    guild_id: Id<GuildMarker>,
+) -> anyhow::Result<()> {
+    let guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+    let settings = &guild_data.flood_guard;
+
+    let yes_or_no = |value: bool| if value { "sim" } else { "não" };
+    let description = format!(
+        "Mensagens enviadas rápido demais não chegam aos agentes, para que ninguém gaste os créditos do servidor com spam. Mensagens seguidas de um mesmo usuário são juntadas em uma só.\n\n**Ativada:** `{}`\n**Máximo por usuário:** `{}` mensagens a cada {} segundos\n**Máximo por chat:** `{}` mensagens a cada {} segundos\n**Ignorar quem floodar:** `{}` (por {} minutos)",
+        yes_or_no(settings.enabled),
+        settings.max_messages_per_user,
+        config::FLOOD_WINDOW_SECONDS,
+        settings.max_messages_per_channel,
+        config::FLOOD_WINDOW_SECONDS,
+        yes_or_no(settings.ignore_flooders),
+        settings.ignore_minutes,
+    );
+
+    let buttons = vec![
+        ButtonBuilder::new()
+            .set_custom_id("cancel")
+            .set_label("Cancelar")
+            .set_style(ButtonStyle::Danger),
+        ButtonBuilder::new()
+            .set_custom_id("toggle_enabled")
+            .set_label(if settings.enabled {
+                "Desativar"
+            } else {
+                "Ativar"
+            }),
+        ButtonBuilder::new()
+            .set_custom_id("limits")
+            .set_label("Alterar Limites"),
+        ButtonBuilder::new()
+            .set_custom_id("toggle_ignore")
+            .set_label(if settings.ignore_flooders {
+                "Não Ignorar"
+            } else {
+                "Ignorar Quem Floodar"
+            }),
+        ButtonBuilder::new()
+            .set_custom_id("ignore_minutes")
+            .set_label("Tempo Ignorado"),
+    ];
+
+    let Some(custom_id) = await_button_choice(
+        ctx,
+        author,
+        Response::from(
+            EmbedBuilder::new_common()
+                .set_color(Color::YELLOW)
+                .set_author(EmbedAuthor {
+                    name: "Proteção contra flood".to_string(),
+                    icon_url: Some(author.avatar_url()),
+                })
+                .set_description(description),
+        ),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let mut guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+    match custom_id.as_str() {
+        "toggle_enabled" => {
+            guild_data.flood_guard.enabled = !guild_data.flood_guard.enabled;
+        }
+        "toggle_ignore" => {
+            guild_data.flood_guard.ignore_flooders = !guild_data.flood_guard.ignore_flooders;
+        }
+        "limits" => {
+            let Ok(Some(limits)) = get_input(
+                ctx,
+                author,
+                Response::new_user_reply(
+                    author,
+                    format!(
+                        "envie o máximo de mensagens por usuário e por chat a cada {} segundos, separados por vírgula (exemplo: `6, 20`):",
+                        config::FLOOD_WINDOW_SECONDS
+                    ),
+                )
+                .add_emoji_prefix("🌊"),
+            )
+            .await
+            else {
+                return Ok(());
+            };
+
+            let limits = limits
+                .split(',')
+                .map(|limit| limit.trim().parse::<u32>())
+                .collect::<Result<Vec<_>, _>>();
+            let Ok([per_user, per_channel]) = limits.as_deref() else {
+                ctx.send(
+                    Response::new_user_reply(
+                        author,
+                        "envie dois números inteiros positivos separados por vírgula!",
+                    )
+                    .add_emoji_prefix(emojis::ERROR),
+                )
+                .await?;
+                return Ok(());
+            };
+
+            guild_data.flood_guard.max_messages_per_user = (*per_user).clamp(1, 100);
+            guild_data.flood_guard.max_messages_per_channel = (*per_channel).clamp(1, 500);
+        }
+        "ignore_minutes" => {
+            let Ok(Some(minutes)) = get_input(
+                ctx,
+                author,
+                Response::new_user_reply(
+                    author,
+                    "por quantos minutos quem floodar deve ser ignorado pelos agentes?",
+                )
+                .add_emoji_prefix("⏳"),
+            )
+            .await
+            else {
+                return Ok(());
+            };
+
+            let Ok(minutes) = minutes.trim().parse::<u32>() else {
+                ctx.send(
+                    Response::new_user_reply(
+                        author,
+                        "o tempo deve ser um número inteiro positivo válido!",
+                    )
+                    .add_emoji_prefix(emojis::ERROR),
+                )
+                .await?;
+                return Ok(());
+            };
+
+            guild_data.flood_guard.ignore_minutes = minutes.clamp(1, 1440) as i64;
+        }
+        _ => return Ok(()),
+    }
+
+    ctx.db().guilds().save(guild_data).await?;
+    ctx.send(
+        Response::new_user_reply(author, "proteção contra flood atualizada!")
+            .add_emoji_prefix(emojis::SUCCESS),
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn settings_dashboard(
+    ctx: &mut CommandContext,
+    author: &User,
+    guild_id: Id<GuildMarker>,
+) -> anyhow::Result<()> {
+    let guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+    let settings = &guild_data.settings;
+
+    let description = format!(
+        "**Máximo de agentes por chat:** `{}`\n**Desligar por inatividade após:** `{} minutos`\n**Prefixos ignorados:** {}\n**Fuso horário:** `{}`\n**Idioma dos agentes:** `{}`\n**Cérebro padrão:** `{}`\n**Conteúdo NSFW:** `{}`",
+        settings.max_agents_per_channel,
+        settings.inactivity_minutes,
+        if settings.ignored_prefixes.is_empty() {
+            "`nenhum`".to_string()
+        } else {
+            settings
+                .ignored_prefixes
+                .iter()
+                .map(|prefix| format!("`{prefix}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+        settings.format_utc_offset(),
+        settings.language.name(),
+        settings.default_brain.name(),
+        settings.nsfw_policy.name(),
+    );
+
+    let buttons = vec![
+        ButtonBuilder::new()
+            .set_custom_id("cancel")
+            .set_label("Cancelar")
+            .set_style(ButtonStyle::Danger),
+        ButtonBuilder::new()
+            .set_custom_id("max_agents")
+            .set_label("Agentes por Chat"),
+        ButtonBuilder::new()
+            .set_custom_id("inactivity")
+            .set_label("Inatividade"),
+        ButtonBuilder::new()
+            .set_custom_id("ignored_prefixes")
+            .set_label("Prefixos Ignorados"),
+        ButtonBuilder::new()
+            .set_custom_id("timezone")
+            .set_label("Fuso Horário"),
+        ButtonBuilder::new()
+            .set_custom_id("language")
+            .set_label("Idioma"),
+        ButtonBuilder::new()
+            .set_custom_id("default_brain")
+            .set_label("Cérebro Padrão"),
+        ButtonBuilder::new()
+            .set_custom_id("nsfw_policy")
+            .set_label("Conteúdo NSFW"),
+    ];
+
+    let Some(custom_id) = await_button_choice(
+        ctx,
+        author,
+        Response::from(
+            EmbedBuilder::new_common()
+                .set_color(Color::CYAN)
+                .set_author(EmbedAuthor {
+                    name: "Configurações do servidor".to_string(),
+                    icon_url: Some(author.avatar_url()),
+                })
+                .set_description(description),
+        ),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let mut guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+    let settings = &mut guild_data.settings;
+    match custom_id.as_str() {
+        "max_agents" | "inactivity" => {
+            let (question, range) = if custom_id == "max_agents" {
+                (
+                    "quantos agentes podem estar invocados ao mesmo tempo em um chat? (1 a 10)",
+                    1..=10,
+                )
+            } else {
+                ("depois de quantos minutos sem mensagens os agentes devem ser desligados? (1 a 120)", 1..=120)
+            };
+
+            let Ok(Some(value)) = get_input(
+                ctx,
+                author,
+                Response::new_user_reply(author, question).add_emoji_prefix("⚙️"),
+            )
+            .await
+            else {
+                return Ok(());
+            };
+
+            let Some(value) = value
+                .trim()
+                .parse::<i64>()
+                .ok()
+                .filter(|v| range.contains(v))
+            else {
+                ctx.send(
+                    Response::new_user_reply(
+                        author,
+                        format!(
+                            "o valor deve ser um número inteiro entre {} e {}!",
+                            range.start(),
+                            range.end()
+                        ),
+                    )
+                    .add_emoji_prefix(emojis::ERROR),
+                )
+                .await?;
+                return Ok(());
+            };
+
+            if custom_id == "max_agents" {
+                settings.max_agents_per_channel = value as u32;
+            } else {
+                settings.inactivity_minutes = value;
+            }
+        }
+        "ignored_prefixes" => {
+            let Ok(Some(prefixes)) = get_input(
+                ctx,
+                author,
+                Response::new_user_reply(
+                    author,
+                    "envie os prefixos de mensagens que os agentes devem ignorar, separados por espaço (exemplo: `> _ !`). Envie `-` para não ignorar nenhum:",
+                )
+                .add_emoji_prefix("🙈"),
+            )
+            .await
+            else {
+                return Ok(());
+            };
+
+            settings.ignored_prefixes = prefixes
+                .split_whitespace()
+                .filter(|prefix| *prefix != "-")
+                .map(|prefix| prefix.chars().take(5).collect())
+                .take(10)
+                .collect();
+        }
+        "timezone" => {
+            let Ok(Some(offset)) = get_input(
+                ctx,
+                author,
+                Response::new_user_reply(
+                    author,
+                    "envie a diferença do fuso horário do servidor para o UTC (exemplos: `-3` para Brasília, `+5:30`, `0`):",
+                )
+                .add_emoji_prefix("🕒"),
+            )
+            .await
+            else {
+                return Ok(());
+            };
+
+            let Some(offset) = parse_utc_offset(&offset) else {
+                ctx.send(
+                    Response::new_user_reply(
+                        author,
+                        "fuso horário inválido! Use um valor entre `-12` e `+14`, como `-3` ou `+5:30`.",
+                    )
+                    .add_emoji_prefix(emojis::ERROR),
+                )
+                .await?;
+                return Ok(());
+            };
+
+            settings.utc_offset_minutes = offset;
+        }
+        "language" => {
+            let buttons = GuildLanguage::ALL
+                .iter()
+                .map(|language| {
+                    ButtonBuilder::new()
+                        .set_custom_id(language.code())
+                        .set_label(language.name())
+                })
+                .collect();
+            let Some(code) = await_button_choice(
+                ctx,
+                author,
+                Response::new_user_reply(author, "escolha o idioma em que os agentes vão falar:")
+                    .add_emoji_prefix("🌐"),
+                buttons,
+            )
+            .await?
+            else {
+                return Ok(());
+            };
+            let Some(language) = GuildLanguage::ALL.into_iter().find(|l| l.code() == code) else {
+                return Ok(());
+            };
+
+            settings.language = language;
+        }
+        "default_brain" => {
+            let buttons = InstanceBrain::ALL
+                .iter()
+                .enumerate()
+                .map(|(index, brain)| {
+                    ButtonBuilder::new()
+                        .set_custom_id(index.to_string())
+                        .set_label(brain.name())
+                })
+                .collect();
+            let Some(index) = await_button_choice(
+                ctx,
+                author,
+                Response::new_user_reply(
+                    author,
+                    "escolha o cérebro usado quando ninguém escolher um:",
+                )
+                .add_emoji_prefix("🧠"),
+                buttons,
+            )
+            .await?
+            else {
+                return Ok(());
+            };
+            let Some(brain) = index
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| InstanceBrain::ALL.get(index).copied())
+            else {
+                return Ok(());
+            };
+
+            settings.default_brain = brain;
+        }
+        "nsfw_policy" => {
+            let buttons = NsfwPolicy::ALL
+                .iter()
+                .enumerate()
+                .map(|(index, policy)| {
+                    ButtonBuilder::new()
+                        .set_custom_id(index.to_string())
+                        .set_label(policy.name())
+                })
+                .collect();
+            let Some(index) = await_button_choice(
+                ctx,
+                author,
+                Response::new_user_reply(
+                    author,
+                    "escolha quando os agentes podem falar sobre conteúdo NSFW:",
+                )
+                .add_emoji_prefix("🔞"),
+                buttons,
+            )
+            .await?
+            else {
+                return Ok(());
+            };
+            let Some(policy) = index
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| NsfwPolicy::ALL.get(index).copied())
+            else {
+                return Ok(());
+            };
+
+            settings.nsfw_policy = policy;
+        }
+        _ => return Ok(()),
+    }
+
+    ctx.db().guilds().save(guild_data).await?;
+    ctx.send(
+        Response::new_user_reply(
+            author,
+            "configurações do servidor atualizadas! Elas valem para os próximos agentes invocados.",
+        )
+        .add_emoji_prefix(emojis::SUCCESS),
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn agent_permissions_dashboard(
+    ctx: &mut CommandContext,
+    author: &User,
+    guild_id: Id<GuildMarker>,
+) -> anyhow::Result<()> {
+    let guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+    let permissions = &guild_data.agent_permissions;
+
+    let mentions = |ids: &[u64], prefix: &str, empty: &str| {
+        if ids.is_empty() {
+            format!("`{empty}`")
+        } else {
+            ids.iter()
+                .map(|id| format!("<{prefix}{id}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    };
+
+    let description = format!(
+        "Administradores do servidor não são afetados por essas regras.\n\n**Chats permitidos:** {}\n**Chats proibidos:** {}\n**Cargos para invocar:** {}\n**Cargos para usar créditos públicos:** {}\n**Cargos para desligar agentes de outras pessoas:** {}",
+        mentions(&permissions.allowed_channel_ids, "#", "todos"),
+        mentions(&permissions.denied_channel_ids, "#", "nenhum"),
+        mentions(&permissions.summon_role_ids, "@&", "qualquer um"),
+        mentions(&permissions.public_credits_role_ids, "@&", "qualquer um"),
+        mentions(
+            &permissions.shutdown_role_ids,
+            "@&",
+            "qualquer um, com autorização de quem invocou"
+        ),
+    );
+
+    let buttons = vec![
+        ButtonBuilder::new()
+            .set_custom_id("cancel")
+            .set_label("Cancelar")
+            .set_style(ButtonStyle::Danger),
+        ButtonBuilder::new()
+            .set_custom_id("allowed_channels")
+            .set_label("Chats Permitidos"),
+        ButtonBuilder::new()
+            .set_custom_id("denied_channels")
+            .set_label("Chats Proibidos"),
+        ButtonBuilder::new()
+            .set_custom_id("summon_roles")
+            .set_label("Cargos para Invocar"),
+        ButtonBuilder::new()
+            .set_custom_id("public_credits_roles")
+            .set_label("Cargos para Créditos Públicos"),
+        ButtonBuilder::new()
+            .set_custom_id("shutdown_roles")
+            .set_label("Cargos para Desligar"),
+    ];
+
+    let Some(custom_id) = await_button_choice(
+        ctx,
+        author,
+        Response::from(
+            EmbedBuilder::new_common()
+                .set_color(Color::CYAN)
+                .set_author(EmbedAuthor {
+                    name: "Permissões dos agentes".to_string(),
+                    icon_url: Some(author.avatar_url()),
+                })
+                .set_description(description),
+        ),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let question = match custom_id.as_str() {
+        "allowed_channels" => "mencione os chats onde agentes podem ser invocados (ou envie os IDs). Envie `-` para permitir todos:",
+        "denied_channels" => "mencione os chats onde agentes não podem ser invocados (ou envie os IDs). Envie `-` para não proibir nenhum:",
+        "summon_roles" | "public_credits_roles" | "shutdown_roles" => "mencione os cargos necessários (ou envie os IDs). Quem tiver qualquer um deles terá a permissão. Envie `-` para remover a exigência:",
+        _ => return Ok(()),
+    };
+
+    let Ok(Some(input)) = get_input(
+        ctx,
+        author,
+        Response::new_user_reply(author, question).add_emoji_prefix("🔐"),
+    )
+    .await
+    else {
+        return Ok(());
+    };
+
+    let ids = parse_ids(&input);
+    let mut guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+    let permissions = &mut guild_data.agent_permissions;
+    match custom_id.as_str() {
+        "allowed_channels" => permissions.allowed_channel_ids = ids,
+        "denied_channels" => permissions.denied_channel_ids = ids,
+        "summon_roles" => permissions.summon_role_ids = ids,
+        "public_credits_roles" => permissions.public_credits_role_ids = ids,
+        _ => permissions.shutdown_role_ids = ids,
+    }
+
+    ctx.db().guilds().save(guild_data).await?;
+    ctx.send(
+        Response::new_user_reply(author, "permissões dos agentes atualizadas!")
+            .add_emoji_prefix(emojis::SUCCESS),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Every ID in a message with channel or role mentions (or plain IDs)
+fn parse_ids(input: &str) -> Vec<u64> {
+    let mut ids = vec![];
+    for id in input
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|id| id.parse::<u64>().ok())
+    {
+        if id != 0 && !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    ids.truncate(25);
+    ids
+}
+
+/// Parses offsets like `-3`, `+5:30` or `UTC-03:00` into minutes
+fn parse_utc_offset(input: &str) -> Option<i32> {
+    let input = input.trim().to_uppercase();
+    let input = input.trim_start_matches("UTC").trim_start_matches("GMT");
+    let (sign, input) = match input.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, input.trim_start_matches('+')),
+    };
+
+    let (hours, minutes) = input.split_once(':').unwrap_or((input, "0"));
+    let hours = hours.trim().parse::<i32>().ok()?;
+    let minutes = minutes.trim().parse::<i32>().ok()?;
+    if !(0..60).contains(&minutes) {
+        return None;
+    }
+
+    let offset = sign * (hours * 60 + minutes);
+    (-12 * 60..=14 * 60).contains(&offset).then_some(offset)
+}
@@ -0,0 +1,212 @@
+use zenis_ai::character_card::CharacterCard;
+use zenis_database::agent_model::{AgentModel, AgentPricing};
+
+use crate::{create_agent::make_agent_identifier, prelude::*};
+
+/// Cards bigger than this are refused before downloading
+const MAX_CARD_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+#[command("Importe um agente de um Character Card (.json ou .png)!")]
+#[name("importar agente")]
+pub async fn import_agent(
+    mut ctx: CommandContext,
+    #[rename("arquivo")]
+    #[description("O Character Card V2 do agente (.json ou .png)")]
+    file: Attachment,
+) -> anyhow::Result<()> {
+    let author = ctx.author().await?;
+    let author_id = author.id;
+
+    let channel = ctx
+        .interaction
+        .channel
+        .clone()
+        .context("Expected a channel ID")?;
+
+    let user_data = ctx.db().users().get_by_user(author_id).await?;
+    if user_data.credits < config::CREATE_AGENT_PRICE {
+        ctx.send(Response::new_user_reply(&author, format!("você não tem suficientes créditos para importar esse agente! Criar agentes custa {} créditos.", config::CREATE_AGENT_PRICE)).add_emoji_prefix(emojis::ERROR)).await?;
+        return Ok(());
+    }
+
+    let card = if file.size > MAX_CARD_FILE_SIZE {
+        None
+    } else {
+        CharacterCard::download(&file.url).await.ok()
+    };
+
+    let Some(card) = card else {
+        ctx.send(
+            Response::new_user_reply(
+                &author,
+                "esse arquivo não é um Character Card válido! Envie um `.json` ou um `.png` no formato Character Card V2.",
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let mut imported = card.to_imported_agent();
+
+    if !config::NAME_SIZE.contains(&imported.name.len()) {
+        ctx.send(
+            Response::new_user_reply(
+                &author,
+                format!(
+                    "o nome do agente deve ter no máximo {} caracteres!",
+                    config::NAME_SIZE.end()
+                ),
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if !config::DESCRIPTION_SIZE.contains(&imported.description.len()) {
+        ctx.send(
+            Response::new_user_reply(
+                &author,
+                format!(
+                    "a descrição do agente (junto com a personalidade e o cenário) deve ter entre {} e {} caracteres, mas o card tem {}!",
+                    config::DESCRIPTION_SIZE.start(),
+                    config::DESCRIPTION_SIZE.end(),
+                    imported.description.len()
+                ),
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut warnings = vec![];
+    if imported.introduction_message.len() > *config::INTRODUCTION_MESSAGE_SIZE.end() {
+        let mut end = *config::INTRODUCTION_MESSAGE_SIZE.end();
+        while !imported.introduction_message.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        imported.introduction_message.truncate(end);
+        warnings.push("a mensagem de introdução foi cortada por ser longa demais");
+    }
+
+    if !config::INTRODUCTION_MESSAGE_SIZE.contains(&imported.introduction_message.len()) {
+        imported.introduction_message = format!("Olá! Eu sou {}.", imported.name);
+        warnings.push("o card não tinha mensagem de introdução, então uma padrão foi usada");
+    }
+
+    let identifier = make_agent_identifier(&author, &imported.name);
+    if ctx
+        .db()
+        .agents()
+        .get_by_identifier(&identifier)
+        .await?
+        .is_some()
+    {
+        ctx.send(Response::new_user_reply(&author, format!("o identificador gerado para o seu agente é **{identifier}**. Mas já existe um agente com esse identificador! Renomeie o agente no card e tente de novo.")).add_emoji_prefix(emojis::ERROR)).await?;
+        return Ok(());
+    }
+
+    // Cards often have multibyte text, so this can't truncate by bytes
+    let display_description = imported.description.chars().take(60).collect::<String>();
+    let display_introduction_message = imported
+        .introduction_message
+        .chars()
+        .take(60)
+        .collect::<String>();
+
+    let mut confirmation_embed = EmbedBuilder::new_common()
+        .set_color(Color::YELLOW)
+        .set_author(EmbedAuthor {
+            name: format!("Importação do agente {}", imported.name),
+            icon_url: Some(author.avatar_url()),
+        })
+        .add_inlined_field("📄 Nome", format!("**{}**", imported.name))
+        .add_inlined_field("😀 Descrição", display_description)
+        .add_inlined_field("📢 Mensagem de introdução", display_introduction_message)
+        .add_inlined_field(
+            "💬 Exemplos de diálogo",
+            imported.example_dialogues.len().to_string(),
+        )
+        .add_inlined_field("📚 Entradas de lore", imported.lore.len().to_string())
+        .add_footer_text(format!("ID do agente: {}", identifier));
+
+    if !warnings.is_empty() {
+        confirmation_embed = confirmation_embed.add_not_inlined_field(
+            "⚠️ Avisos",
+            warnings
+                .iter()
+                .map(|warning| format!("- {warning}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+
+    let confirmation = ctx
+        .helper()
+        .create_confirmation(
+            author_id,
+            false,
+            Response::new_user_reply(
+                &author,
+                format!(
+                    "você quer mesmo importar esse agente? **Criar um agente custa {}₢**.",
+                    config::CREATE_AGENT_PRICE
+                ),
+            )
+            .add_embed(confirmation_embed),
+        )
+        .await?;
+    if !confirmation {
+        return Ok(());
+    }
+
+    let mut user_data = ctx.db().users().get_by_user(author_id).await?;
+    if user_data.credits < config::CREATE_AGENT_PRICE {
+        ctx.send(
+            Response::new_user_reply(
+                &author,
+                "você não tem suficientes créditos para importar esse agente!",
+            )
+            .add_emoji_prefix(emojis::ERROR),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    user_data.remove_credits(config::CREATE_AGENT_PRICE);
+    ctx.db().users().save(user_data).await?;
+
+    let mut agent_model = AgentModel::new(
+        author_id.get(),
+        &identifier,
+        &imported.name,
+        &imported.description,
+        &imported.introduction_message,
+        AgentPricing::default(),
+    )
+    .with_example_dialogues(imported.example_dialogues)
+    .with_lore(imported.lore);
+
+    if let Some(guild_id) = channel.guild_id {
+        agent_model = agent_model.with_guild_id(guild_id.get());
+    }
+
+    ctx.db().agents().create_agent(agent_model).await?;
+
+    ctx.send(
+        Response::new_user_reply(
+            &author,
+            format!(
+                "**{}** foi importado com sucesso! Ao invocar ele, use o ID: `{identifier}`\n-> Use **/configurar agente** para definir a imagem e o preço do agente.\n-> Use **/exportar agente** para levar ele para outras ferramentas.",
+                imported.name
+            ),
+        )
+        .add_emoji_prefix(emojis::SUCCESS),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -1,18 +1,67 @@
 #![allow(clippy::len_zero)]
 use std::time::Duration;
 
+use zenis_ai::brain::make_agent_examples_prompt;
 use zenis_database::{
     agent_model::{AgentModel, AgentPricing},
-    instance_model::{CreditsPaymentMethod, InstanceBrain},
+    earning_model::EarningSource,
+    guild_model::{AgentPermissionSettings, GuildSettings},
+    instance_model::{
+        CreditsPaymentMethod, InstanceBrain, InstanceSettings, SpendingCap, SpendingCapPeriod,
+    },
 };
-use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
+use zenis_discord::twilight_model::{channel::message::component::ButtonStyle, guild::Guild};
 use zenis_framework::{util::make_multiple_rows, watcher::WatcherOptions};
 
 use crate::prelude::*;
 
 #[command("Invoque um agente de IA no chat para conversar com você!")]
 #[name("invocar")]
-pub async fn invoke(mut ctx: CommandContext) -> anyhow::Result<()> {
+pub async fn invoke(
+    mut ctx: CommandContext,
+    #[rename("agente")]
+    #[description("O ID do agente que você quer invocar")]
+    #[autocomplete(suggest_agent_ids)]
+    identifier: Option<String>,
+    #[rename("apenas_menção")]
+    #[description("Responder apenas quando for mencionado ou chamado pelo nome")]
+    mention_only: Option<bool>,
+    #[rename("chance_de_resposta")]
+    #[description("Chance (0-100%) de o agente considerar responder uma mensagem")]
+    reply_probability: Option<i64>,
+    #[rename("atraso_mínimo")]
+    #[description("Tempo mínimo (em segundos) entre as respostas do agente")]
+    min_delay_secs: Option<i64>,
+    #[rename("respostas_por_minuto")]
+    #[description("Máximo de respostas do agente por minuto")]
+    max_replies_per_minute: Option<i64>,
+    #[rename("gasto_máximo")]
+    #[description(
+        "Máximo de créditos que o agente pode gastar com respostas antes de ser desligado"
+    )]
+    max_spend: Option<i64>,
+    #[rename("gasto_por_hora")]
+    #[description("Aplicar o gasto máximo a cada hora, em vez de à sessão inteira")]
+    spend_per_hour: Option<bool>,
+) -> anyhow::Result<()> {
+    let mut settings = build_instance_settings(
+        InstanceSettings::default(),
+        mention_only,
+        reply_probability,
+        min_delay_secs,
+        max_replies_per_minute,
+    );
+    settings.spending_cap = max_spend
+        .filter(|max_spend| *max_spend > 0)
+        .map(|max_credits| SpendingCap {
+            max_credits,
+            period: if spend_per_hour == Some(true) {
+                SpendingCapPeriod::Hour
+            } else {
+                SpendingCapPeriod::Session
+            },
+        });
+
     let author = ctx.author().await?;
     let author_id = author.id;
 
@@ -37,6 +86,20 @@ pub async fn invoke(mut ctx: CommandContext) -> anyhow::Result<()> {
     };
 
     let guild = ctx.client.get_guild(guild_id).await?;
+    let guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+    if !check_summon_permissions(
+        &mut ctx,
+        &author,
+        &guild_data.agent_permissions,
+        (channel.id.get(), channel.parent_id.map(|id| id.get())),
+    )
+    .await?
+    {
+        return Ok(());
+    }
+
+    let guild_settings = guild_data.settings;
+    let max_agents = guild_settings.max_agents_per_channel as usize;
 
     if ctx
         .db()
@@ -44,7 +107,7 @@ pub async fn invoke(mut ctx: CommandContext) -> anyhow::Result<()> {
         .get_all_by_channel(channel.id.get())
         .await?
         .len()
-        > 2
+        >= max_agents
     {
         ctx.reply(
             Response::new_user_reply(&author, "já há muitos agentes neste chat!")
@@ -54,94 +117,17 @@ pub async fn invoke(mut ctx: CommandContext) -> anyhow::Result<()> {
         return Ok(());
     }
 
-    let special_agents = ctx.db().agents().get_all_with_tags(&["special"]).await?;
-
-    let mut buttons = vec![];
-    for agent in special_agents.iter() {
-        buttons.push(
-            ButtonBuilder::new()
-                .set_custom_id(&agent.identifier)
-                .set_label(&agent.name)
-                .set_style(ButtonStyle::Primary),
-        );
-    }
-
-    buttons.push(
-        ButtonBuilder::new()
-            .set_custom_id("custom")
-            .set_label("Agente personalizado"),
-    );
-
-    let message = ctx
-        .send(
-            Response::new_user_reply(&author, "escolha um agente para invocar nesse chat:")
-                .set_components(make_multiple_rows(buttons.clone())),
-        )
-        .await?;
+    let agent_identifier = match identifier {
+        Some(identifier) => identifier.to_lowercase().trim().to_owned(),
+        None => {
+            let Some(agent_identifier) = ask_for_agent_identifier(&mut ctx, &author).await? else {
+                return Ok(());
+            };
 
-    let Ok(Some(interaction)) = ctx
-        .watcher
-        .await_single_component(
-            message.id,
-            move |interaction| interaction.author_id() == Some(author_id),
-            WatcherOptions {
-                timeout: Duration::from_secs(60),
-            },
-        )
-        .await
-    else {
-        return Ok(());
+            agent_identifier
+        }
     };
 
-    let data = interaction.parse_message_component_data()?;
-
-    let buttons = buttons
-        .iter()
-        .map(|b| {
-            let id = b.data.custom_id.as_ref();
-            b.clone()
-                .set_disabled(true)
-                .set_style(if id == Some(&data.custom_id) {
-                    ButtonStyle::Success
-                } else {
-                    ButtonStyle::Secondary
-                })
-        })
-        .collect::<Vec<_>>();
-
-    let mut ctx = CommandContext::from_with_interaction(&ctx, Box::new(interaction));
-    ctx.update_message(Response::default().set_components(make_multiple_rows(buttons)))
-        .await?;
-
-    let mut agent_identifier = data.custom_id.clone();
-
-    if data.custom_id == "custom" {
-        ctx.send(
-            Response::new_user_reply(
-                &author,
-                "escreva o ID do agente personalizado que você quer invocar:",
-            )
-            .add_emoji_prefix("⁉️"),
-        )
-        .await?;
-
-        let Ok(Some(message)) = ctx
-            .watcher
-            .await_single_message(
-                channel.id,
-                move |message| message.author.id == author_id,
-                WatcherOptions {
-                    timeout: Duration::from_secs(60),
-                },
-            )
-            .await
-        else {
-            return Ok(());
-        };
-
-        agent_identifier = message.content.to_lowercase().trim().to_owned();
-    }
-
     let Some(agent) = ctx
         .db()
         .agents()
@@ -171,7 +157,7 @@ pub async fn invoke(mut ctx: CommandContext) -> anyhow::Result<()> {
         return Ok(());
     }
 
-    let brain = ask_for_brain(&mut ctx).await?;
+    let brain = ask_for_brain(&mut ctx, guild_settings.default_brain).await?;
     let mut pricing = agent.pricing;
     pricing.price_per_reply += brain.extra_price_per_reply();
 
@@ -179,7 +165,7 @@ pub async fn invoke(mut ctx: CommandContext) -> anyhow::Result<()> {
 
     if let Some(guild_id) = channel.guild_id {
         if let Some(method) =
-            ask_for_payment_method(&mut ctx, &agent, pricing, author_id, guild_id).await?
+            ask_for_payment_method(&mut ctx, &agent, brain, pricing, author_id, guild_id).await?
         {
             payment_method = method;
         } else {
@@ -187,56 +173,14 @@ pub async fn invoke(mut ctx: CommandContext) -> anyhow::Result<()> {
         }
     }
 
-    let minimum_credits = pricing.price_per_invocation + pricing.price_per_reply;
-
-    match payment_method {
-        CreditsPaymentMethod::UserCredits(user_id) => {
-            let mut user_data = ctx.db().users().get_by_user(Id::new(user_id)).await?;
-            if user_data.credits < minimum_credits {
-                ctx.send(Response::new_user_reply(
-                    &author,
-                    "créditos insuficientes na carteira do usuário para invocar o agente!",
-                ))
-                .await?;
-                return Ok(());
-            }
-
-            user_data.remove_credits(pricing.price_per_invocation);
-        }
-        CreditsPaymentMethod::GuildPublicCredits(guild_id) => {
-            let mut guild_data = ctx.db().guilds().get_by_guild(Id::new(guild_id)).await?;
-            if guild_data.public_credits < minimum_credits {
-                ctx.send(Response::new_user_reply(
-                    &author,
-                    "créditos insuficientes na carteira pública do servidor para invocar o agente!",
-                ))
-                .await?;
-                return Ok(());
-            }
-
-            guild_data.remove_public_credits(pricing.price_per_invocation);
-        }
-    }
-
-    if pricing.price_per_invocation > 0 {
-        let mut creator_data = ctx
-            .db()
-            .users()
-            .get_by_user(Id::new(agent.creator_user_id))
-            .await?;
-        let profit = pricing.price_per_invocation as f64 * 0.025;
-        let profit = profit as i64;
-        creator_data.add_credits(profit);
-        ctx.db().users().save(creator_data).await.ok();
-    }
-
+    // The channel may have changed while the author was choosing, so it's checked again before charging
     let channel_instances = ctx
         .db()
         .instances()
         .get_all_by_channel(channel.id.get())
         .await?;
 
-    if channel_instances.len() > 2 {
+    if channel_instances.len() >= max_agents {
         ctx.send(
             Response::new_user_reply(&author, "já há muitos agentes neste chat!")
                 .add_emoji_prefix(emojis::ERROR),
@@ -257,6 +201,10 @@ pub async fn invoke(mut ctx: CommandContext) -> anyhow::Result<()> {
         return Ok(());
     }
 
+    if !charge_invocation(&mut ctx, &author, pricing, payment_method).await? {
+        return Ok(());
+    }
+
     let mut embed = EmbedBuilder::new_common()
         .set_color(Color::GREEN)
         .set_author(EmbedAuthor {
@@ -267,28 +215,50 @@ pub async fn invoke(mut ctx: CommandContext) -> anyhow::Result<()> {
             "## {} invocado neste chat!\nEnvie mensagens e o agente responderá.",
             agent.name
         ))
-        .add_footer_text(format!("Cérebro: {}", brain.name()));
+        .add_footer_text(format!(
+            "Cérebro: {} ({}₢ por resposta)",
+            brain.name(),
+            pricing.price_per_reply
+        ));
 
     if let Some(image_url) = &agent.agent_url_image {
         embed = embed.set_thumbnail(image_url);
     }
 
+    if let Some(lineage) = &agent.forked_from {
+        embed = embed.add_inlined_field(
+            "🍴 Baseado em",
+            format!("**{}** (`{}`)", lineage.name, lineage.identifier),
+        );
+    }
+
+    if settings != InstanceSettings::default() {
+        embed =
+            embed.add_not_inlined_field("⚙️ Comportamento", format_instance_settings(&settings));
+    }
+
     let message = ctx.send(embed).await?;
-    let system_prompt = format!(
-        "[Zenis Agent System 1.0]\n<!agent_name/>{}\n<!agent_description/>{}\n<!agent_user_id/>%WEBHOOK_ID%\n<!guild>{}\n<!owner_id/>{}", 
-        agent.name, agent.description, guild.name, guild.owner_id
+    let system_prompt = make_system_prompt(
+        &agent,
+        &guild,
+        &guild_settings,
+        channel.nsfw.unwrap_or(false),
     );
 
     let result = ctx
         .client
         .create_agent_instance(
             ctx.db(),
-            brain,
-            (channel.id, author.id),
-            agent.clone(),
-            pricing,
-            payment_method,
-            system_prompt,
+            AgentInstanceRequest {
+                brain,
+                channel_id: channel.id,
+                summoner_id: author.id,
+                agent_model: agent.clone(),
+                pricing,
+                payment_method,
+                settings,
+                system_prompt,
+            },
         )
         .await;
 
@@ -306,20 +276,297 @@ pub async fn invoke(mut ctx: CommandContext) -> anyhow::Result<()> {
             .await
             .ok();
 
+        refund_invocation(&ctx, payment_method, pricing.price_per_invocation).await?;
+
         ctx.send(
-            Response::new_user_reply(&author, "**algo deu errado ao invocar o agente!**\nVerifique se o agente tem um link de imagem PNG válido. Se não for isso, talvez eu não tenha permissão de criar webhooks aqui.\nSe o erro persistir, entre em **/servidoroficial** e busque suporte!")
+            Response::new_user_reply(&author, "**algo deu errado ao invocar o agente!**\nTalvez eu não tenha permissão de criar webhooks aqui. Você não foi cobrado.\nSe o erro persistir, entre em **/servidoroficial** e busque suporte!")
                 .add_emoji_prefix(emojis::ERROR),
         )
         .await?;
         return Ok(());
     }
 
+    ctx.db()
+        .earnings()
+        .credit_creators(
+            &agent,
+            EarningSource::Invocation,
+            pricing.price_per_invocation,
+            payment_method,
+        )
+        .await?;
+
     Ok(())
 }
 
-async fn ask_for_payment_method(
+/// Lets the author pick a special agent, or write the ID of a custom one
+async fn ask_for_agent_identifier(
     ctx: &mut CommandContext,
+    author: &User,
+) -> anyhow::Result<Option<String>> {
+    let special_agents = ctx.db().agents().get_all_with_tags(&["special"]).await?;
+
+    let mut buttons = vec![];
+    for agent in special_agents.iter() {
+        buttons.push(
+            ButtonBuilder::new()
+                .set_custom_id(&agent.identifier)
+                .set_label(&agent.name)
+                .set_style(ButtonStyle::Primary),
+        );
+    }
+
+    buttons.push(
+        ButtonBuilder::new()
+            .set_custom_id("custom")
+            .set_label("Agente personalizado"),
+    );
+
+    let Some(custom_id) = await_button_choice(
+        ctx,
+        author,
+        Response::new_user_reply(author, "escolha um agente para invocar nesse chat:"),
+        buttons,
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    if custom_id != "custom" {
+        return Ok(Some(custom_id));
+    }
+
+    let Ok(Some(identifier)) = get_input(
+        ctx,
+        author,
+        Response::new_user_reply(
+            author,
+            "escreva o ID do agente personalizado que você quer invocar (ou use a opção `agente` do comando):",
+        )
+        .add_emoji_prefix("⁉️"),
+    )
+    .await
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(identifier.to_lowercase()))
+}
+
+/// Replies with an error and returns false if the author can't summon agents in this channel
+pub(crate) async fn check_summon_permissions(
+    ctx: &mut CommandContext,
+    author: &User,
+    permissions: &AgentPermissionSettings,
+    (channel_id, parent_id): (u64, Option<u64>),
+) -> anyhow::Result<bool> {
+    if ctx.author_is_guild_admin() {
+        return Ok(true);
+    }
+
+    let error = if !permissions.allows_channel(channel_id, parent_id) {
+        let mut error = "agentes não podem ser invocados neste chat!".to_string();
+        if !permissions.allowed_channel_ids.is_empty() {
+            error.push_str(&format!(
+                "\nChats permitidos: {}",
+                permissions
+                    .allowed_channel_ids
+                    .iter()
+                    .map(|id| format!("<#{id}>"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        error
+    } else if !AgentPermissionSettings::meets_role_requirement(
+        &permissions.summon_role_ids,
+        &ctx.author_role_ids(),
+    ) {
+        format!(
+            "você precisa de um destes cargos para invocar agentes: {}",
+            format_role_mentions(&permissions.summon_role_ids)
+        )
+    } else {
+        return Ok(true);
+    };
+
+    ctx.reply(
+        Response::new_user_reply(author, error)
+            .add_emoji_prefix(emojis::ERROR)
+            .set_ephemeral(),
+    )
+    .await?;
+
+    Ok(false)
+}
+
+pub(crate) fn format_role_mentions(role_ids: &[u64]) -> String {
+    role_ids
+        .iter()
+        .map(|id| format!("<@&{id}>"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Checks the payer can afford the invocation plus a first reply, and charges the invocation.
+/// Creators are only credited once the agent is summoned, since a failed summon is refunded
+pub(crate) async fn charge_invocation(
+    ctx: &mut CommandContext,
+    author: &User,
+    pricing: AgentPricing,
+    payment_method: CreditsPaymentMethod,
+) -> anyhow::Result<bool> {
+    let minimum_credits = pricing.price_per_invocation + pricing.price_per_reply;
+
+    match payment_method {
+        CreditsPaymentMethod::UserCredits(user_id) => {
+            let mut user_data = ctx.db().users().get_by_user(Id::new(user_id)).await?;
+            if user_data.credits < minimum_credits {
+                ctx.send(Response::new_user_reply(
+                    author,
+                    "créditos insuficientes na carteira do usuário para invocar o agente!",
+                ))
+                .await?;
+                return Ok(false);
+            }
+
+            user_data.remove_credits(pricing.price_per_invocation);
+            ctx.db().users().save(user_data).await?;
+        }
+        CreditsPaymentMethod::GuildPublicCredits(guild_id) => {
+            let mut guild_data = ctx.db().guilds().get_by_guild(Id::new(guild_id)).await?;
+            if guild_data.public_credits < minimum_credits {
+                ctx.send(Response::new_user_reply(
+                    author,
+                    "créditos insuficientes na carteira pública do servidor para invocar o agente!",
+                ))
+                .await?;
+                return Ok(false);
+            }
+
+            guild_data.remove_public_credits(pricing.price_per_invocation);
+            ctx.db().guilds().save(guild_data).await?;
+        }
+    }
+
+    Ok(true)
+}
+
+/// Gives the invocation price back to the payer when the agent couldn't be summoned
+pub(crate) async fn refund_invocation(
+    ctx: &CommandContext,
+    payment_method: CreditsPaymentMethod,
+    price: i64,
+) -> anyhow::Result<()> {
+    match payment_method {
+        CreditsPaymentMethod::UserCredits(user_id) => {
+            let mut user_data = ctx.db().users().get_by_user(Id::new(user_id)).await?;
+            user_data.add_credits(price);
+            ctx.db().users().save(user_data).await?;
+        }
+        CreditsPaymentMethod::GuildPublicCredits(guild_id) => {
+            let mut guild_data = ctx.db().guilds().get_by_guild(Id::new(guild_id)).await?;
+            guild_data.add_public_credits(price);
+            ctx.db().guilds().save(guild_data).await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn make_system_prompt(
     agent: &AgentModel,
+    guild: &Guild,
+    guild_settings: &GuildSettings,
+    is_nsfw_channel: bool,
+) -> String {
+    format!(
+        "[Zenis Agent System 1.0]\n<!agent_name/>{}\n<!agent_description/>{}\n<!agent_user_id/>%WEBHOOK_ID%\n<!guild>{}\n<!owner_id/>{}\n{}{}",
+        agent.name,
+        agent.description,
+        guild.name,
+        guild.owner_id,
+        guild_policy_prompt(guild_settings, is_nsfw_channel),
+        make_agent_examples_prompt(&agent.name, &agent.example_dialogues)
+    )
+}
+
+/// The language and content rules of the guild, as system prompt tags
+pub(crate) fn guild_policy_prompt(guild_settings: &GuildSettings, is_nsfw_channel: bool) -> String {
+    format!(
+        "<!language/>{}\n<!nsfw_allowed/>{}",
+        guild_settings.language.code(),
+        guild_settings.allows_nsfw(is_nsfw_channel)
+    )
+}
+
+pub fn build_instance_settings(
+    mut settings: InstanceSettings,
+    mention_only: Option<bool>,
+    reply_probability: Option<i64>,
+    min_delay_secs: Option<i64>,
+    max_replies_per_minute: Option<i64>,
+) -> InstanceSettings {
+    if let Some(mention_only) = mention_only {
+        settings.mention_only = mention_only;
+    }
+
+    if let Some(reply_probability) = reply_probability {
+        settings.reply_probability = reply_probability.clamp(0, 100) as u8;
+    }
+
+    if let Some(min_delay_secs) = min_delay_secs {
+        settings.min_delay_secs = min_delay_secs.clamp(3, 600);
+    }
+
+    if let Some(max_replies_per_minute) = max_replies_per_minute {
+        // Zero or less removes the limit
+        settings.max_replies_per_minute = if max_replies_per_minute > 0 {
+            Some(max_replies_per_minute.min(30) as u32)
+        } else {
+            None
+        };
+    }
+
+    settings
+}
+
+pub fn format_instance_settings(settings: &InstanceSettings) -> String {
+    format!(
+        "**Apenas menção:** `{}`\n**Chance de resposta:** `{}%`\n**Chance de resposta a agentes:** `{}%`\n**Atraso mínimo:** `{}s`\n**Respostas por minuto:** `{}`\n**Remover ações:** `{}`\n**Limite de gastos:** `{}`",
+        if settings.mention_only { "sim" } else { "não" },
+        settings.reply_probability,
+        settings.bot_reply_probability,
+        settings.min_delay_secs,
+        settings
+            .max_replies_per_minute
+            .map(|max| max.to_string())
+            .unwrap_or_else(|| "sem limite".to_string()),
+        if settings.strip_italic_actions {
+            "sim"
+        } else {
+            "não"
+        },
+        match settings.spending_cap {
+            Some(cap) => format!(
+                "{}₢ {}",
+                cap.max_credits,
+                match cap.period {
+                    SpendingCapPeriod::Session => "por sessão",
+                    SpendingCapPeriod::Hour => "por hora",
+                }
+            ),
+            None => "sem limite".to_string(),
+        }
+    )
+}
+
+pub(crate) async fn ask_for_payment_method(
+    ctx: &mut CommandContext,
+    agent: &AgentModel,
+    brain: InstanceBrain,
     pricing: AgentPricing,
     author_id: Id<UserMarker>,
     guild_id: Id<GuildMarker>,
@@ -354,11 +601,19 @@ async fn ask_for_payment_method(
     let embed = EmbedBuilder::new_common()
         .set_color(Color::YELLOW)
         .set_description(format!(
-            "## {} Escolha quem irá pagar o agente.\nPreço por resposta de **{}**: `{}₢`{invocation_price_str}\n\n🛒 **Quer mais créditos?**\nUse **/comprar**!\n-> Créditos são a moeda que sustenta Zenis e permite que você aproveite o bot!",
+            "## {} Escolha quem irá pagar o agente.\nPreço por resposta de **{}** com o cérebro **{}**: `{}₢`{invocation_price_str}\n\n🛒 **Quer mais créditos?**\nUse **/comprar**!\n-> Créditos são a moeda que sustenta Zenis e permite que você aproveite o bot!",
             emojis::CREDIT,
             agent.name,
+            brain.name(),
             pricing.price_per_reply
         ))
+        .add_not_inlined_field(
+            "🧠 Preço por resposta em cada cérebro",
+            format_reply_prices(&AgentPricing {
+                price_per_reply: pricing.price_per_reply - brain.extra_price_per_reply(),
+                ..pricing
+            }),
+        )
         .add_inlined_field("Sua Carteira", format!("{}₢", user_data.credits))
         .add_inlined_field(
             "Créditos Públicos do Servidor",
@@ -412,6 +667,29 @@ async fn ask_for_payment_method(
     if data.custom_id == "user" {
         return Ok(Some(CreditsPaymentMethod::UserCredits(author_id.get())));
     } else if data.custom_id == "guild" {
+        let required_role_ids = &guild_data.agent_permissions.public_credits_role_ids;
+        if !ctx.author_is_guild_admin()
+            && !AgentPermissionSettings::meets_role_requirement(
+                required_role_ids,
+                &ctx.author_role_ids(),
+            )
+        {
+            let author = ctx.author().await?;
+            ctx.send(
+                Response::new_user_reply(
+                    &author,
+                    format!(
+                        "você precisa de um destes cargos para usar os créditos públicos do servidor: {}",
+                        format_role_mentions(required_role_ids)
+                    ),
+                )
+                .add_emoji_prefix(emojis::ERROR)
+                .set_ephemeral(),
+            )
+            .await?;
+            return Ok(None);
+        }
+
         return Ok(Some(CreditsPaymentMethod::GuildPublicCredits(
             guild_id.get(),
         )));
@@ -420,9 +698,12 @@ async fn ask_for_payment_method(
     Ok(None)
 }
 
-pub async fn ask_for_brain(ctx: &mut CommandContext) -> anyhow::Result<InstanceBrain> {
+pub async fn ask_for_brain(
+    ctx: &mut CommandContext,
+    default_brain: InstanceBrain,
+) -> anyhow::Result<InstanceBrain> {
     if !config::ASK_FOR_BRAIN {
-        return Ok(InstanceBrain::GeminiFlash);
+        return Ok(default_brain);
     }
 
     let author = ctx.author().await?;
@@ -466,7 +747,7 @@ pub async fn ask_for_brain(ctx: &mut CommandContext) -> anyhow::Result<InstanceB
         )
         .await
     else {
-        return Ok(InstanceBrain::ClaudeHaiku);
+        return Ok(default_brain);
     };
 
     let data = interaction.parse_message_component_data()?;
@@ -500,6 +781,6 @@ pub async fn ask_for_brain(ctx: &mut CommandContext) -> anyhow::Result<InstanceB
         "gemini_pro" => Ok(InstanceBrain::GeminiPro),
         "claude_haiku" => Ok(InstanceBrain::ClaudeHaiku),
         "zenis_finetuned" => Ok(InstanceBrain::ZenisFinetuned),
-        _ => Ok(InstanceBrain::ClaudeHaiku),
+        _ => Ok(default_brain),
     }
 }
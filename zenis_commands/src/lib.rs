@@ -21,22 +21,33 @@ macro_rules! register_command {
 }
 
 mod arena;
+mod ask;
+mod ask_about_message;
 mod buy;
 mod common;
 mod configure_agent;
+mod configure_behavior;
+mod continue_session;
 mod create_agent;
+mod earnings;
 mod explore;
+mod export_agent;
+mod export_conversation;
 mod guild;
+mod import_agent;
 mod invite;
 mod invoke;
+mod make_reply;
 mod my_agents;
 mod officialguild;
 mod pay;
+mod rate_agent;
 mod shutdown;
 mod tutorial;
 mod wallet;
 
 mod adm;
+mod agent_versions;
 
 pub type CommandMap = HashMap<String, BoxedCommand>;
 
@@ -45,19 +56,30 @@ pub static COMMANDS: Lazy<CommandMap> = Lazy::new(|| {
 
     register_command!(map, common::PingCommand);
     register_command!(map, invoke::InvokeCommand);
+    register_command!(map, ask::AskCommand);
+    register_command!(map, continue_session::Continue_sessionCommand);
     register_command!(map, wallet::WalletCommand);
+    register_command!(map, earnings::EarningsCommand);
     register_command!(map, guild::GuildCommand);
     register_command!(map, buy::BuyCommand);
     register_command!(map, tutorial::TutorialCommand);
     register_command!(map, invite::InviteCommand);
     register_command!(map, explore::ExploreCommand);
+    register_command!(map, export_conversation::Export_conversationCommand);
     register_command!(map, shutdown::ShutdownCommand);
     register_command!(map, my_agents::My_agentsCommand);
     register_command!(map, create_agent::Create_agentCommand);
+    register_command!(map, import_agent::Import_agentCommand);
+    register_command!(map, export_agent::Export_agentCommand);
     register_command!(map, configure_agent::Configure_agentCommand);
+    register_command!(map, configure_behavior::Configure_behaviorCommand);
     register_command!(map, officialguild::OfficialguildCommand);
     register_command!(map, arena::ArenaCommand);
     register_command!(map, pay::PayCommand);
+    register_command!(map, rate_agent::Rate_agentCommand);
+
+    register_command!(map, ask_about_message::Ask_about_messageCommand);
+    register_command!(map, make_reply::Make_replyCommand);
 
     register_command!(map, adm::AdmCommand);
 
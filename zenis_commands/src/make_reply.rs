@@ -0,0 +1,215 @@
+use chrono::Utc;
+use zenis_database::{
+    guild_model::{AgentPermissionSettings, FloodGuardSettings, GuildSettings},
+    instance_model::InstanceMessage,
+};
+use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
+
+use crate::prelude::*;
+
+#[command("Faça um agente ativo responder a esta mensagem")]
+#[name("Fazer agente responder")]
+#[kind("message")]
+pub async fn make_reply(mut ctx: CommandContext) -> anyhow::Result<()> {
+    let author = ctx.author().await?;
+    let Some(message) = ctx.target_message() else {
+        return Ok(());
+    };
+
+    let channel = ctx
+        .interaction
+        .channel
+        .clone()
+        .context("Expected a channel")?;
+    let instances = ctx
+        .db()
+        .instances()
+        .all_actives_in_channel(channel.id.get())
+        .await?;
+
+    if instances.is_empty() {
+        ctx.reply(
+            Response::new_user_reply(
+                &author,
+                "nenhum agente está invocado neste chat atualmente!",
+            )
+            .add_emoji_prefix(emojis::ERROR)
+            .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if message.content.trim().is_empty() {
+        ctx.reply(
+            Response::new_user_reply(&author, "essa mensagem não tem nenhum texto!")
+                .add_emoji_prefix(emojis::ERROR)
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let guild_data = match ctx.interaction.guild_id {
+        Some(guild_id) => Some(ctx.db().guilds().get_by_guild(guild_id).await?),
+        None => None,
+    };
+
+    // Forced replies are paid by the summoner, so only them, admins and shutdown roles may ask for one
+    let controls_every_agent = ctx.author_is_guild_admin()
+        || guild_data.as_ref().is_some_and(|guild_data| {
+            let required_role_ids = &guild_data.agent_permissions.shutdown_role_ids;
+            !required_role_ids.is_empty()
+                && AgentPermissionSettings::meets_role_requirement(
+                    required_role_ids,
+                    &ctx.author_role_ids(),
+                )
+        });
+    let instances = instances
+        .into_iter()
+        .filter(|instance| controls_every_agent || instance.summoner_id == author.id.get())
+        .collect::<Vec<_>>();
+
+    if instances.is_empty() {
+        ctx.reply(
+            Response::new_user_reply(
+                &author,
+                "você só pode fazer responder os agentes que você invocou!",
+            )
+            .add_emoji_prefix(emojis::ERROR)
+            .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let flood_guard_settings = guild_data
+        .as_ref()
+        .map(|guild_data| guild_data.flood_guard.clone())
+        .unwrap_or_else(FloodGuardSettings::default);
+    let flood_check = ctx.client.flood_guard.check(
+        channel.id.get(),
+        author.id.get(),
+        &flood_guard_settings,
+        Utc::now().timestamp_millis(),
+    );
+    if flood_check.verdict == FloodVerdict::Drop {
+        ctx.reply(
+            Response::new_user_reply(
+                &author,
+                "você está pedindo respostas rápido demais! Espere um pouco.",
+            )
+            .add_emoji_prefix(emojis::ERROR)
+            .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let instance = if instances.len() == 1 {
+        instances[0].clone()
+    } else {
+        let buttons = instances
+            .iter()
+            .map(|instance| {
+                ButtonBuilder::new()
+                    .set_custom_id(instance.id.to_hex())
+                    .set_label(&instance.agent_name)
+                    .set_style(ButtonStyle::Secondary)
+            })
+            .collect::<Vec<_>>();
+
+        let Some(custom_id) = await_button_choice(
+            &mut ctx,
+            &author,
+            Response::new_user_reply(&author, "escolha o agente que vai responder:")
+                .add_emoji_prefix("🤖"),
+            buttons,
+        )
+        .await?
+        else {
+            return Ok(());
+        };
+
+        let Some(instance) = instances
+            .iter()
+            .find(|instance| instance.id.to_hex() == custom_id)
+        else {
+            return Ok(());
+        };
+
+        instance.clone()
+    };
+
+    // Reload it, since the agent may have replied while the user was choosing
+    let Some(mut instance) = ctx.db().instances().get_by_id(instance.id).await? else {
+        return Ok(());
+    };
+
+    if !instance.active || instance.exit_reason.is_some() {
+        ctx.reply(
+            Response::new_user_reply(&author, "esse agente já foi desligado!")
+                .add_emoji_prefix(emojis::ERROR)
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let guild_settings = guild_data
+        .map(|guild_data| guild_data.settings)
+        .unwrap_or_else(GuildSettings::default);
+
+    let request = format!(
+        "<!system_instruction/>{} pediu para você responder diretamente a esta mensagem.",
+        author.display_name()
+    );
+
+    let content = message.content.chars().take(1000).collect::<String>();
+    let formated_content = format!(
+        "<!name/>{}\n<!user/>@{}\n<!user_id/>{}\n<!date/>{}\n<!message_id/>{}\n<!channel/>#{}\n<!channel_id/>{}\n<!message/>{}\n{}",
+        message.author.display_name(),
+        message.author.name,
+        message.author.id,
+        guild_settings.format_date(Utc::now()),
+        message.id,
+        channel.name.clone().unwrap_or(String::from("n-a")),
+        channel.id,
+        content,
+        request
+    );
+
+    // The message may already be in the history, then only the request is added
+    let already_in_history = instance.contains_user_message(message.id.get());
+    instance.push_message(InstanceMessage {
+        is_assistant: false,
+        text: if already_in_history {
+            request
+        } else {
+            formated_content
+        },
+        user_id: if already_in_history {
+            author.id.get()
+        } else {
+            message.author.id.get()
+        },
+        image_url: None,
+        message_id: None,
+    });
+    instance.is_awaiting_new_messages = false;
+
+    let agent_name = instance.agent_name.clone();
+    ctx.db().instances().save(instance).await?;
+
+    ctx.reply(
+        Response::new_user_reply(
+            &author,
+            format!("**{agent_name}** vai responder a essa mensagem em breve!"),
+        )
+        .add_emoji_prefix(emojis::SUCCESS)
+        .set_ephemeral(),
+    )
+    .await?;
+
+    Ok(())
+}
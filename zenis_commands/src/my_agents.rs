@@ -39,11 +39,16 @@ pub async fn my_agents(mut ctx: CommandContext) -> anyhow::Result<()> {
             let mut display_description = agent.description.clone();
             display_description.truncate(30);
 
+            let lineage = match &agent.forked_from {
+                Some(lineage) => format!("\nBaseado em: `{}`", lineage.identifier),
+                None => String::new(),
+            };
+
             page = page.add_not_inlined_field(
                 &agent.name,
                 format!(
-                    "ID: `{}`\nDescrição: `{}...`\nPreço por invocação: `{}₢`\nPúblico: `{}`\n\nInvocações totais: `{}`\nRespostas totais: `{}`",
-                    agent.identifier, display_description, agent.pricing.price_per_invocation, if agent.public { "Sim" } else { "Não" }, agent.stats.invocations, agent.stats.replies
+                    "ID: `{}`\nDescrição: `{}...`\nPreço por invocação: `{}₢`\nPúblico: `{}`{}\n\nInvocações totais: `{}`\nRespostas totais: `{}`\nVariantes: `{}`",
+                    agent.identifier, display_description, agent.pricing.price_per_invocation, if agent.public { "Sim" } else { "Não" }, lineage, agent.stats.invocations, agent.stats.replies, agent.stats.forks
                 )
             );
         }
@@ -2,6 +2,7 @@
 pub use zenis_common::*;
 pub use zenis_data::*;
 pub use zenis_discord::twilight_model::application::command::*;
+pub use zenis_discord::twilight_model::channel::Attachment;
 pub use zenis_discord::twilight_model::http::attachment::Attachment as DiscordAttachment;
 pub use zenis_discord::twilight_model::{
     id::{marker::*, *},
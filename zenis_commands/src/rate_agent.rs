@@ -0,0 +1,145 @@
+use zenis_database::review_model::ReviewModel;
+
+use crate::prelude::*;
+
+#[command("Avalie um agente com quem você conversou!")]
+#[name("avaliar agente")]
+pub async fn rate_agent(
+    mut ctx: CommandContext,
+    #[rename("id")]
+    #[description("O ID do agente que você quer avaliar")]
+    #[autocomplete(suggest_agent_ids)]
+    identifier: String,
+    #[rename("nota")]
+    #[description("Sua nota para o agente, de 1 a 5")]
+    rating: i64,
+    #[rename("comentário")]
+    #[description("Uma avaliação curta sobre o agente")]
+    comment: Option<String>,
+) -> anyhow::Result<()> {
+    let author = ctx.author().await?;
+    let author_id = author.id.get();
+
+    let Some(mut agent) = ctx.db().agents().get_by_identifier(&identifier).await? else {
+        ctx.reply(
+            Response::new_user_reply(&author, "agente inválido ou inexistente")
+                .add_emoji_prefix(emojis::ERROR)
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    if !(1..=5).contains(&rating) {
+        ctx.reply(
+            Response::new_user_reply(&author, "a nota deve ser um número de 1 a 5!")
+                .add_emoji_prefix(emojis::ERROR)
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let comment = comment
+        .map(|comment| comment.trim().replace('\n', " "))
+        .filter(|comment| !comment.is_empty());
+    if comment
+        .as_ref()
+        .is_some_and(|comment| !config::REVIEW_COMMENT_SIZE.contains(&comment.chars().count()))
+    {
+        ctx.reply(
+            Response::new_user_reply(
+                &author,
+                format!(
+                    "o comentário deve ter no máximo {} caracteres!",
+                    config::REVIEW_COMMENT_SIZE.end()
+                ),
+            )
+            .add_emoji_prefix(emojis::ERROR)
+            .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if agent.creator_user_id == author_id {
+        ctx.reply(
+            Response::new_user_reply(&author, "você não pode avaliar um agente seu!")
+                .add_emoji_prefix(emojis::ERROR)
+                .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let db = ctx.db();
+    let has_chatted = db
+        .instances()
+        .has_user_chatted_with(author_id, &agent.identifier)
+        .await?
+        || db
+            .archived_instances()
+            .has_user_chatted_with(author_id, &agent.identifier)
+            .await?
+        || db
+            .sessions()
+            .has_user_saved_with(author_id, &agent.identifier)
+            .await?;
+
+    if !has_chatted {
+        ctx.reply(
+            Response::new_user_reply(
+                &author,
+                "você só pode avaliar agentes com quem conversou recentemente!",
+            )
+            .add_emoji_prefix(emojis::ERROR)
+            .set_ephemeral(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let rating = rating as u8;
+    let previous_review = db
+        .reviews()
+        .get_by_agent_and_user(&agent.identifier, author_id)
+        .await?;
+    let previous_rating = previous_review.as_ref().map(|review| review.rating);
+
+    let review = match previous_review {
+        Some(mut review) => {
+            review.update(rating, comment);
+            db.reviews().save(review.clone()).await?;
+            review
+        }
+        None => {
+            let review = ReviewModel::new(&agent.identifier, author_id, rating, comment);
+            db.reviews().create_review(review.clone()).await?;
+            review
+        }
+    };
+
+    agent.stats.apply_rating(rating, previous_rating);
+    db.agents().save(agent.clone()).await?;
+
+    ctx.reply(
+        Response::new_user_reply(
+            &author,
+            format!(
+                "sua avaliação de **{}** foi {}! {}",
+                agent.name,
+                if previous_rating.is_some() {
+                    "atualizada"
+                } else {
+                    "enviada"
+                },
+                review.stars()
+            ),
+        )
+        .add_emoji_prefix(emojis::SUCCESS)
+        .set_ephemeral(),
+    )
+    .await?;
+
+    Ok(())
+}
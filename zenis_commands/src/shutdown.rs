@@ -1,10 +1,7 @@
-use std::time::Duration;
-
-use zenis_database::instance_model::InstanceModel;
+use zenis_database::{guild_model::AgentPermissionSettings, instance_model::InstanceModel};
 use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
-use zenis_framework::{util::make_multiple_rows, watcher::WatcherOptions};
 
-use crate::prelude::*;
+use crate::{invoke::format_role_mentions, prelude::*};
 
 #[command("Desligue os agentes nesse chat!")]
 #[name("desligar")]
@@ -49,55 +46,25 @@ pub async fn shutdown(mut ctx: CommandContext) -> anyhow::Result<()> {
         );
     }
 
-    let message = ctx
-        .send(
-            Response::new_user_reply(&author, "escolha um agente para desligar:")
-                .add_emoji_prefix("🤖")
-                .set_components(make_multiple_rows(buttons.clone())),
-        )
-        .await?;
-
-    let Ok(Some(interaction)) = ctx
-        .watcher
-        .await_single_component(
-            message.id,
-            move |interaction| interaction.author_id() == Some(author.id),
-            WatcherOptions {
-                timeout: Duration::from_secs(60),
-            },
-        )
-        .await
+    let Some(custom_id) = await_button_choice(
+        &mut ctx,
+        &author,
+        Response::new_user_reply(&author, "escolha um agente para desligar:")
+            .add_emoji_prefix("🤖"),
+        buttons,
+    )
+    .await?
     else {
         return Ok(());
     };
 
-    let data = interaction.parse_message_component_data()?;
-
-    let buttons = buttons
-        .iter()
-        .map(|b| {
-            let id = b.data.custom_id.as_ref();
-            b.clone()
-                .set_disabled(true)
-                .set_style(if id == Some(&data.custom_id) {
-                    ButtonStyle::Success
-                } else {
-                    ButtonStyle::Secondary
-                })
-        })
-        .collect::<Vec<_>>();
-
-    let mut ctx = CommandContext::from_with_interaction(&ctx, Box::new(interaction));
-    ctx.update_message(Response::default().set_components(make_multiple_rows(buttons)))
-        .await?;
-
-    if data.custom_id == "cancel" {
+    if custom_id == "cancel" {
         return Ok(());
     }
 
     let Some(instance) = instances
         .iter()
-        .find(|instance| instance.agent_identifier == data.custom_id)
+        .find(|instance| instance.agent_identifier == custom_id)
     else {
         return Ok(());
     };
@@ -121,7 +88,39 @@ async fn shutdown_instance(
         return Ok(());
     };
 
-    if instance.summoner_id != author.id.get() {
+    // Members with a shutdown role don't need the summoner's authorization
+    let mut needs_authorization = instance.summoner_id != author.id.get();
+    if needs_authorization {
+        if let Some(guild_id) = ctx.interaction.guild_id {
+            let guild_data = ctx.db().guilds().get_by_guild(guild_id).await?;
+            let required_role_ids = &guild_data.agent_permissions.shutdown_role_ids;
+
+            if !required_role_ids.is_empty() {
+                if AgentPermissionSettings::meets_role_requirement(
+                    required_role_ids,
+                    &ctx.author_role_ids(),
+                ) {
+                    needs_authorization = false;
+                } else if !ctx.author_is_guild_admin() {
+                    ctx.send(
+                        Response::new_user_reply(
+                            author,
+                            format!(
+                                "você precisa de um destes cargos para desligar agentes invocados por outras pessoas: {}",
+                                format_role_mentions(required_role_ids)
+                            ),
+                        )
+                        .add_emoji_prefix(emojis::ERROR)
+                        .set_ephemeral(),
+                    )
+                    .await?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    if needs_authorization {
         let summoner = ctx.client.get_user(Id::new(instance.summoner_id)).await?;
         let confirmation = ctx
             .helper()
@@ -6,6 +6,10 @@ Aqui está os principais comandos que você precisa saber:
 
 **/carteira** -> `mostra seus créditos no bot`
 **/invocar** -> `invoca um agente de IA no chat para conversar`
+**/continuar** -> `traz de volta um agente desligado, continuando a sessão de onde parou`
+**/perguntar** -> `faz uma pergunta rápida para um agente sem invocá-lo`
+**/configurar comportamento** -> `ajusta quando e quanto um agente que você invocou responde`
+**/exportar conversa** -> `baixa a conversa atual ou recente com um agente em JSON, Markdown ou HTML`
 **/arena** -> `batalhe com outros usuários usando IA e sua criatividade`
 **/servidor** -> `mostra a carteira do servidor`
 **/criar agente** -> `gasta créditos para criar um agente para você`
@@ -13,7 +17,9 @@ Aqui está os principais comandos que você precisa saber:
 **/convidar** -> `convide Zenis para o seu servidor`
 **/servidoroficial** -> `entre no servidor oficial do ZenisAI`
 
-**/comprar** -> `compre créditos para aproveitar Zenis`"#;
+**/comprar** -> `compre créditos para aproveitar Zenis`
+
+Clique com o botão direito em uma mensagem e vá em **Apps** para perguntar a um agente sobre ela ou fazer um agente ativo respondê-la!"#;
 
 #[command("Veja os comandos principais de ZenisAI!")]
 #[name("tutorial")]
@@ -1,7 +1,9 @@
 use std::time::Duration;
 
 use zenis_data::products::PRODUCTS;
-use zenis_framework::watcher::WatcherOptions;
+use zenis_database::{agent_model::AgentPricing, instance_model::InstanceBrain};
+use zenis_discord::twilight_model::channel::message::component::ButtonStyle;
+use zenis_framework::{util::make_multiple_rows, watcher::WatcherOptions};
 
 use crate::prelude::*;
 
@@ -76,3 +78,157 @@ pub async fn get_input(
 
     Ok(Some(message.content.trim().to_owned()))
 }
+
+/// Sends a private button prompt and returns the chosen custom ID. `ctx` is replaced by the button interaction
+pub async fn await_button_choice(
+    ctx: &mut CommandContext,
+    author: &User,
+    response: Response,
+    buttons: Vec<ButtonBuilder>,
+) -> anyhow::Result<Option<String>> {
+    let author_id = author.id;
+    let response = response
+        .set_components(make_multiple_rows(buttons.clone()))
+        .set_ephemeral();
+    let message = if ctx.already_replied {
+        ctx.followup_interaction(response).await?.model().await?
+    } else {
+        ctx.send(response).await?
+    };
+
+    let Ok(Some(interaction)) = ctx
+        .watcher
+        .await_single_component(
+            message.id,
+            move |interaction| interaction.author_id() == Some(author_id),
+            WatcherOptions {
+                timeout: Duration::from_secs(60),
+            },
+        )
+        .await
+    else {
+        return Ok(None);
+    };
+
+    let data = interaction.parse_message_component_data()?;
+
+    let buttons = buttons
+        .iter()
+        .map(|b| {
+            let id = b.data.custom_id.as_ref();
+            b.clone()
+                .set_disabled(true)
+                .set_style(if id == Some(&data.custom_id) {
+                    ButtonStyle::Success
+                } else {
+                    ButtonStyle::Secondary
+                })
+        })
+        .collect::<Vec<_>>();
+
+    *ctx = CommandContext::from_with_interaction(ctx, Box::new(interaction));
+    ctx.update_message(Response::default().set_components(make_multiple_rows(buttons)))
+        .await?;
+
+    Ok(Some(data.custom_id))
+}
+
+/// The cost of a reply with each brain, like "Gemini 2.5 Flash `5₢` · Gemini 2.5 Pro `7₢`"
+pub fn format_reply_prices(pricing: &AgentPricing) -> String {
+    InstanceBrain::ALL
+        .iter()
+        .map(|brain| format!("{} `{}₢`", brain.name(), brain.price_per_reply(pricing)))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// Autocomplete provider for options taking an agent ID, using the agent search
+pub async fn suggest_agent_ids(
+    ctx: &CommandContext,
+    query: String,
+) -> anyhow::Result<Vec<CommandOptionChoice>> {
+    // Discord accepts at most 25 choices
+    let agents = ctx
+        .db()
+        .agents()
+        .search(
+            &query,
+            ctx.author_id().get(),
+            ctx.interaction.guild_id.map(|id| id.get()),
+            25,
+        )
+        .await?;
+
+    Ok(agents
+        .into_iter()
+        .map(|agent| {
+            let name = format!("{} ({})", agent.name, agent.identifier);
+            make_string_choice(name, agent.identifier)
+        })
+        .collect())
+}
+
+/// Autocomplete provider for options taking the ID of an agent the user created, for commands only its creator can use
+pub async fn suggest_own_agent_ids(
+    ctx: &CommandContext,
+    query: String,
+) -> anyhow::Result<Vec<CommandOptionChoice>> {
+    let agents = ctx
+        .db()
+        .agents()
+        .get_all_by_creator(ctx.author_id().get())
+        .await?;
+
+    let mut agents = agents
+        .into_iter()
+        .filter_map(|agent| {
+            let score =
+                fuzzy_score(&query, &agent.name).max(fuzzy_score(&query, &agent.identifier))?;
+            Some((score, agent))
+        })
+        .collect::<Vec<_>>();
+    agents.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    Ok(agents
+        .into_iter()
+        .take(25)
+        .map(|(_, agent)| {
+            let name = format!("{} ({})", agent.name, agent.identifier);
+            make_string_choice(name, agent.identifier)
+        })
+        .collect())
+}
+
+/// Autocomplete provider for options taking the name of one of the user's saved sessions
+pub async fn suggest_session_names(
+    ctx: &CommandContext,
+    query: String,
+) -> anyhow::Result<Vec<CommandOptionChoice>> {
+    let sessions = ctx
+        .db()
+        .sessions()
+        .get_all_by_owner(ctx.author_id().get())
+        .await?;
+
+    let mut sessions = sessions
+        .into_iter()
+        .filter_map(|session| Some((fuzzy_score(&query, &session.name)?, session)))
+        .collect::<Vec<_>>();
+    // The sort is stable, so equal scores keep the most recently played first
+    sessions.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    Ok(sessions
+        .into_iter()
+        .take(25)
+        .map(|(_, session)| make_string_choice(session.name.clone(), session.name))
+        .collect())
+}
+
+/// Discord limits choice names and values to 100 characters
+fn make_string_choice(name: String, value: String) -> CommandOptionChoice {
+    CommandOptionChoice {
+        name: name.chars().take(100).collect(),
+        name_localizations: None,
+        value: CommandOptionChoiceValue::String(value.chars().take(100).collect()),
+    }
+}
@@ -1,5 +1,7 @@
 use std::ops::RangeInclusive;
 
+use once_cell::sync::Lazy;
+
 pub const BOT_IDS: &[u64] = &[1215409249262379018];
 pub const DEBUG_GUILD_ID: u64 = 562364424002994189;
 
@@ -13,6 +15,65 @@ pub const ARENA_DESCRIPTION_SIZE: RangeInclusive<usize> = 1..=300;
 pub const NAME_SIZE: RangeInclusive<usize> = 1..=32;
 pub const DESCRIPTION_SIZE: RangeInclusive<usize> = 1..=1500;
 pub const INTRODUCTION_MESSAGE_SIZE: RangeInclusive<usize> = 1..=312;
+pub const REVIEW_COMMENT_SIZE: RangeInclusive<usize> = 1..=300;
+pub const EXAMPLE_DIALOGUE_MESSAGE_SIZE: RangeInclusive<usize> = 1..=500;
+/// Example dialogues of an agent sent to the brain (and editable in /configurar agente)
+pub const MAX_EXAMPLE_DIALOGUES: usize = 5;
+
+/// How long the in-memory list of public agents used by searches is kept before reloading it
+pub const AGENT_SEARCH_INDEX_TTL_SECONDS: i64 = 60;
+
+/// Prices creators may set for their agents. The reply price excludes the brain surcharge
+pub const PRICE_PER_INVOCATION_RANGE: RangeInclusive<i64> = 0..=100;
+pub const PRICE_PER_REPLY_RANGE: RangeInclusive<i64> = 1..=25;
 
 pub const CREATE_AGENT_PRICE: i64 = 0;
 pub const PUBLISH_AGENT_PRICE: i64 = 0;
+/// Reasons offered to admins when rejecting an agent in the review queue (at most 4)
+pub const AGENT_REJECTION_REASONS: &[&str] = &[
+    "Descrição muito curta ou incompleta",
+    "Conteúdo impróprio ou ofensivo",
+    "Agente duplicado ou muito parecido com outro",
+    "Nome ou imagem inadequados",
+];
+/// Share (in %) of what is charged for invoking an agent that goes to its creator.
+/// The revenue shares can be changed with environment variables of the same name
+pub static CREATOR_INVOCATION_REVENUE_SHARE_PERCENT: Lazy<i64> =
+    Lazy::new(|| percent_from_env("CREATOR_INVOCATION_REVENUE_SHARE_PERCENT", 50));
+/// Share (in %) of the agent's own price per reply (brain and image extras excluded) that goes to its creator
+pub static CREATOR_REPLY_REVENUE_SHARE_PERCENT: Lazy<i64> =
+    Lazy::new(|| percent_from_env("CREATOR_REPLY_REVENUE_SHARE_PERCENT", 20));
+/// Share (in %) of a fork's creator earnings that goes to the original creator, when the fork opted in
+pub static FORK_REVENUE_SHARE_PERCENT: Lazy<i64> =
+    Lazy::new(|| percent_from_env("FORK_REVENUE_SHARE_PERCENT", 30));
+/// How many days of earnings /ganhos shows
+pub const EARNINGS_DASHBOARD_DAYS: i64 = 7;
+
+/// How long ended conversations stay archived (and exportable with /exportar) before being purged
+pub const ARCHIVED_CONVERSATION_RETENTION_HOURS: i64 = 72;
+
+/// Oldest saved sessions of a user are dropped past this amount
+pub const MAX_SESSIONS_PER_USER: usize = 25;
+
+/// An agent reply that couldn't be delivered after this many attempts is dropped (and never charged)
+pub const OUTBOX_MAX_ATTEMPTS: u32 = 5;
+
+/// Window in which the flood guard counts the messages of a user and of a channel
+pub const FLOOD_WINDOW_SECONDS: i64 = 30;
+/// Messages of the same user sent closer than this are merged into a single history entry
+pub const FLOOD_MERGE_SECONDS: i64 = 4;
+/// Minimum interval between flood notices in the same channel
+pub const FLOOD_NOTICE_COOLDOWN_SECONDS: i64 = 300;
+
+/// Channels whose leftover webhooks are cleaned up at a time on startup, with a pause between batches
+pub const WEBHOOK_RECONCILE_BATCH_SIZE: usize = 10;
+pub const WEBHOOK_RECONCILE_BATCH_INTERVAL_SECONDS: u64 = 5;
+
+/// A percentage from an environment variable, falling back to `default` when it's missing or out of 0..=100
+fn percent_from_env(name: &str, default: i64) -> i64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .filter(|percent| (0..=100).contains(percent))
+        .unwrap_or(default)
+}
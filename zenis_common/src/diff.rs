@@ -0,0 +1,128 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffChunk {
+    Equal(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Word by word differences turning `old` into `new`. Chunks keep the whitespace after each word,
+/// which doesn't count when comparing them
+pub fn diff_words(old: &str, new: &str) -> Vec<DiffChunk> {
+    let old_words = old.split_inclusive(char::is_whitespace).collect::<Vec<_>>();
+    let new_words = new.split_inclusive(char::is_whitespace).collect::<Vec<_>>();
+
+    // Length of the longest common subsequence of old_words[i..] and new_words[j..]
+    let mut lcs = vec![vec![0usize; new_words.len() + 1]; old_words.len() + 1];
+    for i in (0..old_words.len()).rev() {
+        for j in (0..new_words.len()).rev() {
+            lcs[i][j] = if old_words[i].trim_end() == new_words[j].trim_end() {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut chunks: Vec<DiffChunk> = vec![];
+    let mut push = |chunk: DiffChunk| match (chunks.last_mut(), chunk) {
+        (Some(DiffChunk::Equal(last)), DiffChunk::Equal(word))
+        | (Some(DiffChunk::Removed(last)), DiffChunk::Removed(word))
+        | (Some(DiffChunk::Added(last)), DiffChunk::Added(word)) => last.push_str(&word),
+        (_, chunk) => chunks.push(chunk),
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_words.len() && j < new_words.len() {
+        if old_words[i].trim_end() == new_words[j].trim_end() {
+            push(DiffChunk::Equal(new_words[j].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            push(DiffChunk::Removed(old_words[i].to_string()));
+            i += 1;
+        } else {
+            push(DiffChunk::Added(new_words[j].to_string()));
+            j += 1;
+        }
+    }
+
+    for word in &old_words[i..] {
+        push(DiffChunk::Removed(word.to_string()));
+    }
+
+    for word in &new_words[j..] {
+        push(DiffChunk::Added(word.to_string()));
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_inputs() {
+        assert_eq!(diff_words("", ""), vec![]);
+        assert_eq!(
+            diff_words("", "um dois"),
+            vec![DiffChunk::Added("um dois".into())]
+        );
+        assert_eq!(
+            diff_words("um dois", ""),
+            vec![DiffChunk::Removed("um dois".into())]
+        );
+    }
+
+    #[test]
+    fn equal_texts() {
+        assert_eq!(
+            diff_words("o gato dorme", "o gato dorme"),
+            vec![DiffChunk::Equal("o gato dorme".into())]
+        );
+    }
+
+    #[test]
+    fn pure_insertion() {
+        assert_eq!(
+            diff_words("o gato dorme", "o gato preto dorme"),
+            vec![
+                DiffChunk::Equal("o gato ".into()),
+                DiffChunk::Added("preto ".into()),
+                DiffChunk::Equal("dorme".into()),
+            ]
+        );
+        assert_eq!(
+            diff_words("o gato", "o gato dorme"),
+            vec![
+                DiffChunk::Equal("o gato ".into()),
+                DiffChunk::Added("dorme".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn pure_removal() {
+        assert_eq!(
+            diff_words("o gato preto dorme", "o gato dorme"),
+            vec![
+                DiffChunk::Equal("o gato ".into()),
+                DiffChunk::Removed("preto ".into()),
+                DiffChunk::Equal("dorme".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn consecutive_words_are_merged_into_one_chunk() {
+        assert_eq!(
+            diff_words("a b c d", "a x y d"),
+            vec![
+                DiffChunk::Equal("a ".into()),
+                DiffChunk::Removed("b c ".into()),
+                DiffChunk::Added("x y ".into()),
+                DiffChunk::Equal("d".into()),
+            ]
+        );
+    }
+}
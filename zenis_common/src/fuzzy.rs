@@ -0,0 +1,153 @@
+use crate::clear_string;
+
+/// How well `query` matches a short `text` (like a name), ignoring case and accents. Higher is better,
+/// `None` means no match. Falls back to matching the query letters in order, so "mnk" finds "Monki"
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let query = clear_string(query.trim());
+    let text = clear_string(text);
+
+    fuzzy_word_score_cleared(&query, &text).or_else(|| {
+        let gaps = subsequence_gaps(&query.replace(' ', ""), &text)?;
+        Some(100u32.saturating_sub(gaps as u32).max(1))
+    })
+}
+
+/// Like [`fuzzy_score`] but only matching whole words (with typos), which suits long texts like descriptions
+pub fn fuzzy_word_score(query: &str, text: &str) -> Option<u32> {
+    fuzzy_word_score_cleared(&clear_string(query.trim()), &clear_string(text))
+}
+
+fn fuzzy_word_score_cleared(query: &str, text: &str) -> Option<u32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    if text == query {
+        return Some(1000);
+    }
+
+    if text.starts_with(query) {
+        return Some(800);
+    }
+
+    if let Some(position) = text.find(query) {
+        let at_word_start = text[..position]
+            .chars()
+            .last()
+            .is_none_or(|c| !c.is_alphanumeric());
+        return Some(if at_word_start { 600 } else { 500 });
+    }
+
+    let text_words = words(text);
+    let mut total = 0;
+    for query_word in words(query) {
+        let max_typos = match query_word.chars().count() {
+            0..=3 => 0,
+            4..=6 => 1,
+            _ => 2,
+        };
+
+        let best = text_words
+            .iter()
+            .filter_map(|text_word| {
+                if text_word.starts_with(query_word) {
+                    Some(3)
+                } else if levenshtein(query_word, text_word) <= max_typos {
+                    Some(2)
+                } else {
+                    None
+                }
+            })
+            .max()?;
+
+        total += best;
+    }
+
+    Some(200 + total * 10)
+}
+
+fn words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Total distance between the query letters found in order in `text`
+fn subsequence_gaps(query: &str, text: &str) -> Option<usize> {
+    let mut text_chars = text.chars().enumerate();
+    let mut last_position: Option<usize> = None;
+    let mut gaps = 0;
+
+    for query_char in query.chars() {
+        let (position, _) = text_chars.find(|(_, c)| *c == query_char)?;
+        if let Some(last_position) = last_position {
+            gaps += position - last_position - 1;
+        }
+
+        last_position = Some(position);
+    }
+
+    Some(gaps)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let cost = if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn better_matches_rank_higher() {
+        let scores = [
+            fuzzy_score("monki", "Monki"),
+            fuzzy_score("mon", "Monki"),
+            fuzzy_score("monki", "zenis/monki"),
+            fuzzy_score("onki", "Monki"),
+            fuzzy_score("monky", "Monki"),
+            fuzzy_score("mnk", "Monki"),
+        ]
+        .map(Option::unwrap);
+
+        assert!(
+            scores.windows(2).all(|pair| pair[0] > pair[1]),
+            "{scores:?}"
+        );
+    }
+
+    #[test]
+    fn case_and_accents_are_ignored() {
+        assert_eq!(fuzzy_score("JOAO", "João"), Some(1000));
+        assert_eq!(fuzzy_word_score("cafe", "Um café quente"), Some(600));
+    }
+
+    #[test]
+    fn unrelated_text_has_no_score() {
+        assert_eq!(fuzzy_score("xyz", "Monki"), None);
+        assert_eq!(fuzzy_score("knom", "Monki"), None);
+        assert_eq!(fuzzy_word_score("mnk", "Monki"), None);
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(fuzzy_score("", "Monki"), Some(0));
+        assert_eq!(fuzzy_score("   ", "Monki"), Some(0));
+        assert_eq!(fuzzy_word_score("", "Monki"), Some(0));
+    }
+}
@@ -1,6 +1,8 @@
 mod cache;
 mod color;
 pub mod config;
+mod diff;
+mod fuzzy;
 mod identifiable;
 mod image;
 mod pagination;
@@ -8,6 +10,8 @@ mod probability;
 
 pub use cache::Cache;
 pub use color::Color;
+pub use diff::{diff_words, DiffChunk};
+pub use fuzzy::{fuzzy_score, fuzzy_word_score};
 pub use identifiable::Identifiable;
 pub use image::*;
 pub use pagination::Pagination;
@@ -1,15 +1,22 @@
-use std::hash::Hash;
+use std::{hash::Hash, sync::Mutex};
 
 use bson::{doc, oid::ObjectId, Document};
 use mongodb::Collection;
 use once_cell::sync::Lazy;
 use tokio_stream::StreamExt;
-use zenis_common::Cache;
+use zenis_common::{config, Cache};
 
 use crate::{agent_model::AgentModel, common::query_by_id, ZenisDatabase};
 
 static CACHE_ID: Lazy<Cache<ObjectId, AgentModel>> = Lazy::new(|| Cache::new(1000));
 static CACHE_IDENTIFIER: Lazy<Cache<String, AgentModel>> = Lazy::new(|| Cache::new(1000));
+static SEARCH_INDEX: Lazy<Mutex<Option<SearchIndex>>> = Lazy::new(|| Mutex::new(None));
+
+/// Public agents as loaded at `loaded_at`, searched in memory
+struct SearchIndex {
+    loaded_at: i64,
+    agents: Vec<AgentModel>,
+}
 
 #[allow(unused)]
 pub struct AgentCommands {
@@ -135,6 +142,79 @@ impl AgentCommands {
             .await?)
     }
 
+    /// Agents with a pending publish request, oldest first
+    pub async fn get_all_waiting_for_approval(&self) -> anyhow::Result<Vec<AgentModel>> {
+        let query = doc! {
+            "is_waiting_for_approval": true,
+        };
+
+        Ok(self
+            .collection
+            .find(query)
+            .sort(doc! { "_id": 1 })
+            .await?
+            .collect::<Result<Vec<_>, _>>()
+            .await?)
+    }
+
+    async fn get_search_index(&self) -> anyhow::Result<Vec<AgentModel>> {
+        let now = chrono::Utc::now().timestamp();
+        if let Some(index) = SEARCH_INDEX.lock().unwrap().as_ref() {
+            if now - index.loaded_at < config::AGENT_SEARCH_INDEX_TTL_SECONDS {
+                return Ok(index.agents.clone());
+            }
+        }
+
+        let agents = self.get_all_public().await?;
+        *SEARCH_INDEX.lock().unwrap() = Some(SearchIndex {
+            loaded_at: now,
+            agents: agents.clone(),
+        });
+        Ok(agents)
+    }
+
+    /// Public agents plus the private ones of the user and of the guild, most relevant first
+    pub async fn search(
+        &self,
+        query: &str,
+        user_id: u64,
+        guild_id: Option<u64>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<AgentModel>> {
+        let mut agents = self.get_search_index().await?;
+        agents.append(&mut self.get_all_by_creator(user_id).await?);
+        if let Some(guild_id) = guild_id {
+            agents.append(&mut self.get_all_private_by_guild(guild_id).await?);
+        }
+
+        let mut results: Vec<(u32, AgentModel)> = vec![];
+        for agent in agents {
+            if results
+                .iter()
+                .any(|(_, a)| a.identifier == agent.identifier)
+            {
+                continue;
+            }
+
+            if let Some(score) = agent.search_score(query) {
+                results.push((score, agent));
+            }
+        }
+
+        // Among equally relevant agents, the most invoked come first
+        results.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .cmp(a_score)
+                .then(b.stats.invocations.cmp(&a.stats.invocations))
+        });
+
+        Ok(results
+            .into_iter()
+            .take(limit)
+            .map(|(_, agent)| agent)
+            .collect())
+    }
+
     pub async fn create_agent(&self, agent_model: AgentModel) -> anyhow::Result<()> {
         if self
             .get_by_identifier(&agent_model.identifier)
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use zenis_common::{fuzzy_score, fuzzy_word_score};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct AgentPricing {
@@ -18,10 +19,74 @@ impl Default for AgentPricing {
     }
 }
 
+impl AgentPricing {
+    /// A one-shot question costs a single reply plus the invocation, since no instance is kept alive
+    pub fn price_per_question(&self) -> i64 {
+        self.price_per_reply + self.price_per_invocation
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AgentStats {
     pub invocations: u64,
     pub replies: u64,
+    #[serde(default = "Default::default")]
+    pub forks: u64,
+    #[serde(default = "Default::default")]
+    pub rating_sum: u64,
+    #[serde(default = "Default::default")]
+    pub rating_count: u64,
+}
+
+impl AgentStats {
+    pub fn average_rating(&self) -> Option<f64> {
+        (self.rating_count > 0).then(|| self.rating_sum as f64 / self.rating_count as f64)
+    }
+
+    /// Adds a rating, replacing `previous` when the user had already rated the agent
+    pub fn apply_rating(&mut self, rating: u8, previous: Option<u8>) {
+        match previous {
+            Some(previous) => {
+                self.rating_sum = self.rating_sum.saturating_sub(previous as u64);
+            }
+            None => self.rating_count += 1,
+        }
+
+        self.rating_sum += rating as u64;
+    }
+}
+
+/// The agent another one was forked from
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AgentLineage {
+    pub identifier: String,
+    pub name: String,
+    pub creator_user_id: u64,
+    /// Whether the original creator gets part of the fork's invocation profit
+    pub shares_revenue: bool,
+}
+
+/// A sample exchange showing how the agent talks
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AgentExampleDialogue {
+    pub user_message: String,
+    pub agent_message: String,
+}
+
+/// Background knowledge about the agent's world, related to some keywords
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AgentLoreEntry {
+    pub keys: Vec<String>,
+    pub content: String,
+}
+
+/// An admin's decision on a request to publish the agent
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AgentModerationDecision {
+    pub reviewer_user_id: u64,
+    pub approved: bool,
+    pub reason: Option<String>,
+    pub decided_at_timestamp: i64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -36,9 +101,20 @@ pub struct AgentModel {
     pub introduction_message: String,
     pub agent_url_image: Option<String>,
     pub pricing: AgentPricing,
+    #[serde(default = "Default::default")]
+    pub example_dialogues: Vec<AgentExampleDialogue>,
+    #[serde(default = "Default::default")]
+    pub lore: Vec<AgentLoreEntry>,
+    #[serde(default = "Default::default")]
+    pub forked_from: Option<AgentLineage>,
 
     pub public: bool,
     pub is_waiting_for_approval: bool,
+    /// Admin that took the pending publish request from the review queue
+    #[serde(default = "Default::default")]
+    pub reviewer_user_id: Option<u64>,
+    #[serde(default = "Default::default")]
+    pub moderation_history: Vec<AgentModerationDecision>,
     pub tags: HashSet<String>,
 
     pub stats: AgentStats,
@@ -63,14 +139,22 @@ impl AgentModel {
             introduction_message: introduction_message.to_string(),
             agent_url_image: None,
             pricing,
+            example_dialogues: vec![],
+            lore: vec![],
+            forked_from: None,
 
             public: false,
             is_waiting_for_approval: false,
+            reviewer_user_id: None,
+            moderation_history: vec![],
             tags: HashSet::new(),
 
             stats: AgentStats {
                 invocations: 0,
                 replies: 0,
+                forks: 0,
+                rating_sum: 0,
+                rating_count: 0,
             },
         }
     }
@@ -89,4 +173,89 @@ impl AgentModel {
         self.tags = tags.into_iter().map(|t| t.to_string()).collect();
         self
     }
+
+    pub fn with_example_dialogues(mut self, example_dialogues: Vec<AgentExampleDialogue>) -> Self {
+        self.example_dialogues = example_dialogues;
+        self
+    }
+
+    pub fn with_lore(mut self, lore: Vec<AgentLoreEntry>) -> Self {
+        self.lore = lore;
+        self
+    }
+
+    /// How relevant the agent is for a search. Names and IDs weigh more than tags, and tags more than the description
+    pub fn search_score(&self, query: &str) -> Option<u32> {
+        [
+            fuzzy_score(query, &self.name).map(|score| score * 3),
+            fuzzy_score(query, &self.identifier).map(|score| score * 3),
+            self.tags
+                .iter()
+                .filter_map(|tag| fuzzy_score(query, tag))
+                .max()
+                .map(|score| score * 2),
+            fuzzy_word_score(query, &self.description),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+    }
+
+    pub fn submit_for_approval(&mut self) {
+        self.is_waiting_for_approval = true;
+        self.reviewer_user_id = None;
+    }
+
+    /// Ends the pending publish request, making the agent public if approved, and records the decision
+    pub fn decide_approval(
+        &mut self,
+        reviewer_user_id: u64,
+        approved: bool,
+        reason: Option<String>,
+    ) {
+        self.is_waiting_for_approval = false;
+        self.reviewer_user_id = None;
+        self.public = approved;
+        self.moderation_history.push(AgentModerationDecision {
+            reviewer_user_id,
+            approved,
+            reason,
+            decided_at_timestamp: chrono::Utc::now().timestamp(),
+        });
+    }
+
+    pub fn with_forked_from(mut self, forked_from: AgentLineage) -> Self {
+        self.forked_from = Some(forked_from);
+        self
+    }
+
+    /// A private copy of this agent owned by `creator_user_id`, keeping the lineage
+    pub fn fork(
+        &self,
+        creator_user_id: u64,
+        identifier: impl ToString,
+        name: impl ToString,
+        shares_revenue: bool,
+    ) -> Self {
+        let mut fork = Self::new(
+            creator_user_id,
+            identifier,
+            name,
+            &self.description,
+            &self.introduction_message,
+            AgentPricing::default(),
+        )
+        .with_example_dialogues(self.example_dialogues.clone())
+        .with_lore(self.lore.clone())
+        .with_tags(self.tags.iter())
+        .with_forked_from(AgentLineage {
+            identifier: self.identifier.clone(),
+            name: self.name.clone(),
+            creator_user_id: self.creator_user_id,
+            shares_revenue,
+        });
+
+        fork.agent_url_image = self.agent_url_image.clone();
+        fork
+    }
 }
@@ -0,0 +1,90 @@
+use bson::doc;
+use mongodb::Collection;
+use tokio_stream::StreamExt;
+
+use crate::{
+    agent_model::AgentModel,
+    agent_version_model::{AgentVersionContent, AgentVersionField, AgentVersionModel},
+    ZenisDatabase,
+};
+
+#[allow(unused)]
+pub struct AgentVersionCommands {
+    pub collection: Collection<AgentVersionModel>,
+    db: ZenisDatabase,
+}
+
+impl AgentVersionCommands {
+    pub const fn new(collection: Collection<AgentVersionModel>, db: ZenisDatabase) -> Self {
+        Self { collection, db }
+    }
+
+    /// Versions of an agent, most recent first
+    pub async fn get_all_by_agent(
+        &self,
+        agent_identifier: &str,
+    ) -> anyhow::Result<Vec<AgentVersionModel>> {
+        Ok(self
+            .collection
+            .find(doc! { "agent_identifier": agent_identifier })
+            .sort(doc! { "number": -1 })
+            .await?
+            .collect::<Result<Vec<_>, _>>()
+            .await?)
+    }
+
+    /// Saves `after` as a new version if it differs from `before`, returning the changed fields.
+    /// The first change of an agent also saves `before` as its initial version
+    pub async fn record_change(
+        &self,
+        before: &AgentModel,
+        after: &AgentModel,
+        author_user_id: u64,
+        restored_from: Option<u32>,
+    ) -> anyhow::Result<Vec<AgentVersionField>> {
+        let before_content = AgentVersionContent::from_agent(before);
+        let after_content = AgentVersionContent::from_agent(after);
+
+        let changed_fields = before_content.changed_fields(&after_content);
+        if changed_fields.is_empty() {
+            return Ok(changed_fields);
+        }
+
+        let latest = self
+            .collection
+            .find_one(doc! { "agent_identifier": &after.identifier })
+            .sort(doc! { "number": -1 })
+            .await?;
+
+        let number = match latest {
+            Some(latest) => latest.number + 1,
+            None => {
+                self.collection
+                    .insert_one(AgentVersionModel::new(
+                        &before.identifier,
+                        1,
+                        before.creator_user_id,
+                        vec![],
+                        before_content,
+                    ))
+                    .await?;
+                2
+            }
+        };
+
+        let mut version = AgentVersionModel::new(
+            &after.identifier,
+            number,
+            author_user_id,
+            changed_fields.clone(),
+            after_content,
+        );
+        if let Some(restored_from) = restored_from {
+            version = version.with_restored_from(restored_from);
+        }
+
+        self.collection.insert_one(version).await?;
+
+        Ok(changed_fields)
+    }
+}
@@ -0,0 +1,123 @@
+use bson::oid::ObjectId;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::agent_model::{AgentExampleDialogue, AgentModel, AgentPricing};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AgentVersionField {
+    Description,
+    IntroductionMessage,
+    Image,
+    Pricing,
+    ExampleDialogues,
+}
+
+impl AgentVersionField {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Description => "Descrição",
+            Self::IntroductionMessage => "Mensagem de introdução",
+            Self::Image => "Imagem",
+            Self::Pricing => "Preços",
+            Self::ExampleDialogues => "Exemplos de diálogo",
+        }
+    }
+}
+
+/// What the creator can edit in an agent, as it was in some version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentVersionContent {
+    pub description: String,
+    pub introduction_message: String,
+    pub agent_url_image: Option<String>,
+    pub pricing: AgentPricing,
+    pub example_dialogues: Vec<AgentExampleDialogue>,
+}
+
+impl AgentVersionContent {
+    pub fn from_agent(agent: &AgentModel) -> Self {
+        Self {
+            description: agent.description.clone(),
+            introduction_message: agent.introduction_message.clone(),
+            agent_url_image: agent.agent_url_image.clone(),
+            pricing: agent.pricing,
+            example_dialogues: agent.example_dialogues.clone(),
+        }
+    }
+
+    pub fn apply_to(&self, agent: &mut AgentModel) {
+        agent.description = self.description.clone();
+        agent.introduction_message = self.introduction_message.clone();
+        agent.agent_url_image = self.agent_url_image.clone();
+        agent.pricing = self.pricing;
+        agent.example_dialogues = self.example_dialogues.clone();
+    }
+
+    pub fn changed_fields(&self, other: &Self) -> Vec<AgentVersionField> {
+        [
+            (
+                self.description != other.description,
+                AgentVersionField::Description,
+            ),
+            (
+                self.introduction_message != other.introduction_message,
+                AgentVersionField::IntroductionMessage,
+            ),
+            (
+                self.agent_url_image != other.agent_url_image,
+                AgentVersionField::Image,
+            ),
+            (self.pricing != other.pricing, AgentVersionField::Pricing),
+            (
+                self.example_dialogues != other.example_dialogues,
+                AgentVersionField::ExampleDialogues,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(changed, field)| changed.then_some(field))
+        .collect()
+    }
+}
+
+/// A saved state of an agent, recorded whenever its creator changes it. Numbers start at 1 for each agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentVersionModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub agent_identifier: String,
+    pub number: u32,
+    pub author_user_id: u64,
+    /// Fields changed from the previous version. Empty in the first one
+    pub changed_fields: Vec<AgentVersionField>,
+    /// The version this one rolled the agent back to, if any
+    pub restored_from: Option<u32>,
+    pub content: AgentVersionContent,
+    pub created_at_timestamp: i64,
+}
+
+impl AgentVersionModel {
+    pub fn new(
+        agent_identifier: impl ToString,
+        number: u32,
+        author_user_id: u64,
+        changed_fields: Vec<AgentVersionField>,
+        content: AgentVersionContent,
+    ) -> Self {
+        Self {
+            id: ObjectId::new(),
+            agent_identifier: agent_identifier.to_string(),
+            number,
+            author_user_id,
+            changed_fields,
+            restored_from: None,
+            content,
+            created_at_timestamp: Utc::now().timestamp(),
+        }
+    }
+
+    pub fn with_restored_from(mut self, restored_from: u32) -> Self {
+        self.restored_from = Some(restored_from);
+        self
+    }
+}
@@ -0,0 +1,86 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::Collection;
+use tokio_stream::StreamExt;
+
+use crate::{
+    archived_instance_model::ArchivedInstanceModel,
+    common::{query_by_id, query_user_chatted_with},
+    ZenisDatabase,
+};
+
+#[allow(unused)]
+pub struct ArchivedInstanceCommands {
+    pub collection: Collection<ArchivedInstanceModel>,
+    db: ZenisDatabase,
+}
+
+impl ArchivedInstanceCommands {
+    pub const fn new(collection: Collection<ArchivedInstanceModel>, db: ZenisDatabase) -> Self {
+        Self { collection, db }
+    }
+
+    pub async fn create_archive(&self, archive: ArchivedInstanceModel) -> anyhow::Result<()> {
+        self.collection.insert_one(archive).await?;
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, id: ObjectId) -> anyhow::Result<Option<ArchivedInstanceModel>> {
+        Ok(self.collection.find_one(query_by_id(id)).await?)
+    }
+
+    /// Most recently ended conversations of a channel, newest first
+    pub async fn get_recent_by_channel(
+        &self,
+        channel_id: u64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<ArchivedInstanceModel>> {
+        let now = chrono::Utc::now().timestamp();
+        let query = doc! {
+            "channel_id": channel_id as i64,
+            "expires_at_timestamp": { "$gt": now },
+        };
+
+        Ok(self
+            .collection
+            .find(query)
+            .sort(doc! { "archived_at_timestamp": -1 })
+            .limit(limit)
+            .await?
+            .collect::<Result<Vec<_>, _>>()
+            .await?)
+    }
+
+    /// Channels that had a conversation archived
+    pub async fn get_all_channel_ids(&self) -> anyhow::Result<Vec<u64>> {
+        Ok(self
+            .collection
+            .distinct("channel_id", doc! {})
+            .await?
+            .into_iter()
+            .filter_map(|channel_id| channel_id.as_i64())
+            .map(|channel_id| channel_id as u64)
+            .collect())
+    }
+
+    pub async fn delete_expired_archives(&self) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        self.collection
+            .delete_many(doc! { "expires_at_timestamp": { "$lt": now } })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Whether the user summoned or talked to the agent in one of these conversations
+    pub async fn has_user_chatted_with(
+        &self,
+        user_id: u64,
+        agent_identifier: &str,
+    ) -> anyhow::Result<bool> {
+        Ok(self
+            .collection
+            .count_documents(query_user_chatted_with(user_id, agent_identifier))
+            .await?
+            > 0)
+    }
+}
@@ -0,0 +1,44 @@
+use bson::oid::ObjectId;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use zenis_common::config;
+
+use crate::instance_model::{InstanceMessage, InstanceModel};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedInstanceModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub instance_id: ObjectId,
+    pub summoner_id: u64,
+    pub channel_id: u64,
+    pub agent_identifier: String,
+    pub agent_name: String,
+    pub exit_reason: Option<String>,
+    pub history: Vec<InstanceMessage>,
+
+    pub archived_at_timestamp: i64,
+    pub expires_at_timestamp: i64,
+}
+
+impl ArchivedInstanceModel {
+    pub fn new(instance: &InstanceModel) -> Self {
+        let now = Utc::now();
+        Self {
+            id: ObjectId::new(),
+            instance_id: instance.id,
+            summoner_id: instance.summoner_id,
+            channel_id: instance.channel_id,
+            agent_identifier: instance.agent_identifier.clone(),
+            agent_name: instance.agent_name.clone(),
+            exit_reason: instance.exit_reason.clone(),
+            history: instance.history.clone(),
+
+            archived_at_timestamp: now.timestamp(),
+            expires_at_timestamp: (now
+                + chrono::Duration::try_hours(config::ARCHIVED_CONVERSATION_RETENTION_HOURS)
+                    .unwrap_or_default())
+            .timestamp(),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use bson::doc;
+use mongodb::Collection;
+use tokio_stream::StreamExt;
+
+use crate::{channel_webhook_model::ChannelWebhookModel, common::query_by_id, ZenisDatabase};
+
+#[allow(unused)]
+pub struct ChannelWebhookCommands {
+    pub collection: Collection<ChannelWebhookModel>,
+    db: ZenisDatabase,
+}
+
+impl ChannelWebhookCommands {
+    pub const fn new(collection: Collection<ChannelWebhookModel>, db: ZenisDatabase) -> Self {
+        Self { collection, db }
+    }
+
+    pub async fn create_webhook(&self, webhook: ChannelWebhookModel) -> anyhow::Result<()> {
+        self.collection.insert_one(webhook).await?;
+        Ok(())
+    }
+
+    pub async fn get_by_channel(
+        &self,
+        channel_id: u64,
+    ) -> anyhow::Result<Option<ChannelWebhookModel>> {
+        Ok(self
+            .collection
+            .find_one(doc! { "channel_id": channel_id as i64 })
+            .await?)
+    }
+
+    pub async fn get_all(&self) -> anyhow::Result<Vec<ChannelWebhookModel>> {
+        Ok(self
+            .collection
+            .find(doc! {})
+            .await?
+            .collect::<Result<Vec<_>, _>>()
+            .await?)
+    }
+
+    pub async fn delete_webhook(&self, webhook: &ChannelWebhookModel) -> anyhow::Result<()> {
+        self.collection.delete_one(query_by_id(webhook.id)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+/// The bot-owned webhook shared by every agent of a channel. Agents set their name and avatar per message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelWebhookModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub channel_id: u64,
+    pub guild_id: Option<u64>,
+    pub webhook_id: u64,
+    pub webhook_token: String,
+}
+
+impl ChannelWebhookModel {
+    pub fn new(
+        channel_id: u64,
+        guild_id: Option<u64>,
+        (webhook_id, webhook_token): (u64, String),
+    ) -> Self {
+        Self {
+            id: ObjectId::new(),
+            channel_id,
+            guild_id,
+            webhook_id,
+            webhook_token,
+        }
+    }
+}
@@ -8,6 +8,17 @@ pub fn query_by_id(id: ObjectId) -> Document {
     }
 }
 
+/// Conversations (live or archived) the user summoned or talked to the agent in
+pub fn query_user_chatted_with(user_id: u64, agent_identifier: &str) -> Document {
+    doc! {
+        "agent_identifier": agent_identifier,
+        "$or": [
+            { "summoner_id": user_id as i64 },
+            { "history": { "$elemMatch": { "user_id": user_id as i64, "is_assistant": false } } },
+        ],
+    }
+}
+
 pub fn _default_now() -> DatabaseDateTime {
     DatabaseDateTime(chrono::Utc::now())
 }
@@ -0,0 +1,114 @@
+use bson::doc;
+use mongodb::Collection;
+use tokio_stream::StreamExt;
+use zenis_common::config;
+use zenis_discord::twilight_model::id::Id;
+
+use crate::{
+    agent_model::AgentModel,
+    earning_model::{EarningModel, EarningSource},
+    instance_model::CreditsPaymentMethod,
+    ZenisDatabase,
+};
+
+#[allow(unused)]
+pub struct EarningCommands {
+    pub collection: Collection<EarningModel>,
+    db: ZenisDatabase,
+}
+
+impl EarningCommands {
+    pub const fn new(collection: Collection<EarningModel>, db: ZenisDatabase) -> Self {
+        Self { collection, db }
+    }
+
+    pub async fn create_earning(&self, earning: EarningModel) -> anyhow::Result<()> {
+        self.collection.insert_one(earning).await?;
+        Ok(())
+    }
+
+    /// Earnings of a creator since `since_timestamp`, most recent first
+    pub async fn get_all_by_creator_since(
+        &self,
+        creator_user_id: u64,
+        since_timestamp: i64,
+    ) -> anyhow::Result<Vec<EarningModel>> {
+        Ok(self
+            .collection
+            .find(doc! {
+                "creator_user_id": creator_user_id as i64,
+                "created_at_timestamp": { "$gte": since_timestamp },
+            })
+            .sort(doc! { "created_at_timestamp": -1 })
+            .await?
+            .collect::<Result<Vec<_>, _>>()
+            .await?)
+    }
+
+    /// Credits the creator's share of a charge on `agent`, splitting it with the original creator
+    /// when the agent is a fork that opted in, and records it in the ledger. Private agents and
+    /// creators paying for their own agents earn nothing, so credits can't be farmed
+    pub async fn credit_creators(
+        &self,
+        agent: &AgentModel,
+        source: EarningSource,
+        charged: i64,
+        payer: CreditsPaymentMethod,
+    ) -> anyhow::Result<()> {
+        let paid_by = |user_id: u64| payer == CreditsPaymentMethod::UserCredits(user_id);
+        if !agent.public || paid_by(agent.creator_user_id) {
+            return Ok(());
+        }
+
+        let mut amount = charged * source.revenue_share_percent() / 100;
+        if amount <= 0 {
+            return Ok(());
+        }
+
+        if let Some(lineage) = agent.forked_from.as_ref().filter(|l| {
+            l.shares_revenue
+                && l.creator_user_id != agent.creator_user_id
+                && !paid_by(l.creator_user_id)
+        }) {
+            let original_amount = amount * *config::FORK_REVENUE_SHARE_PERCENT / 100;
+            amount -= original_amount;
+
+            if original_amount > 0 {
+                self.credit(
+                    lineage.creator_user_id,
+                    EarningModel::new(
+                        lineage.creator_user_id,
+                        &agent.identifier,
+                        &agent.name,
+                        source,
+                        charged,
+                        original_amount,
+                    )
+                    .as_fork_share(),
+                )
+                .await?;
+            }
+        }
+
+        self.credit(
+            agent.creator_user_id,
+            EarningModel::new(
+                agent.creator_user_id,
+                &agent.identifier,
+                &agent.name,
+                source,
+                charged,
+                amount,
+            ),
+        )
+        .await
+    }
+
+    async fn credit(&self, user_id: u64, earning: EarningModel) -> anyhow::Result<()> {
+        let mut user_data = self.db.users().get_by_user(Id::new(user_id)).await?;
+        user_data.add_credits(earning.amount);
+        self.db.users().save(user_data).await?;
+
+        self.create_earning(earning).await
+    }
+}
@@ -0,0 +1,65 @@
+use bson::oid::ObjectId;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use zenis_common::config;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EarningSource {
+    Invocation,
+    Reply,
+}
+
+impl EarningSource {
+    /// Share (in %) of the charge that goes to the agent's creator
+    pub fn revenue_share_percent(&self) -> i64 {
+        match self {
+            Self::Invocation => *config::CREATOR_INVOCATION_REVENUE_SHARE_PERCENT,
+            Self::Reply => *config::CREATOR_REPLY_REVENUE_SHARE_PERCENT,
+        }
+    }
+}
+
+/// Ledger entry of credits paid to a creator for a charge on one of their agents
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EarningModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub creator_user_id: u64,
+    pub agent_identifier: String,
+    pub agent_name: String,
+    pub source: EarningSource,
+    /// What the payer was charged, of which `amount` went to the creator
+    pub charged: i64,
+    pub amount: i64,
+    /// Whether the agent is a fork and this is the original creator's part of its earnings
+    pub is_fork_share: bool,
+    pub created_at_timestamp: i64,
+}
+
+impl EarningModel {
+    pub fn new(
+        creator_user_id: u64,
+        agent_identifier: impl ToString,
+        agent_name: impl ToString,
+        source: EarningSource,
+        charged: i64,
+        amount: i64,
+    ) -> Self {
+        Self {
+            id: ObjectId::new(),
+            creator_user_id,
+            agent_identifier: agent_identifier.to_string(),
+            agent_name: agent_name.to_string(),
+            source,
+            charged,
+            amount,
+            is_fork_share: false,
+            created_at_timestamp: Utc::now().timestamp(),
+        }
+    }
+
+    pub fn as_fork_share(mut self) -> Self {
+        self.is_fork_share = true;
+        self
+    }
+}
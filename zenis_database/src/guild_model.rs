@@ -1,14 +1,204 @@
 use std::collections::HashSet;
 
+use chrono::{DateTime, FixedOffset, Utc};
 use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 use zenis_discord::twilight_model::id::{marker::GuildMarker, Id};
 
+use crate::instance_model::InstanceBrain;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum GuildFlag {
     AlreadyAknowledged,
 }
 
+/// What agents are allowed to post in the guild
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct OutputSafetySettings {
+    pub block_invites: bool,
+    pub block_links: bool,
+    /// Domains still allowed when links are blocked
+    pub allowed_domains: Vec<String>,
+}
+
+impl Default for OutputSafetySettings {
+    fn default() -> Self {
+        Self {
+            block_invites: true,
+            block_links: false,
+            allowed_domains: vec![],
+        }
+    }
+}
+
+/// Limits on what enters the history of agents, so a single user can't make them reply non-stop
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct FloodGuardSettings {
+    pub enabled: bool,
+    /// Per user, in each channel, inside the flood window
+    pub max_messages_per_user: u32,
+    pub max_messages_per_channel: u32,
+    /// Users over the limit are ignored by the agents of the channel for a while
+    pub ignore_flooders: bool,
+    pub ignore_minutes: i64,
+}
+
+impl Default for FloodGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_messages_per_user: 6,
+            max_messages_per_channel: 20,
+            ignore_flooders: false,
+            ignore_minutes: 5,
+        }
+    }
+}
+
+/// Who can summon and shut down agents, and where. Empty lists don't restrict anything
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct AgentPermissionSettings {
+    /// When not empty, agents can only be summoned in these channels (or their threads)
+    pub allowed_channel_ids: Vec<u64>,
+    pub denied_channel_ids: Vec<u64>,
+    pub summon_role_ids: Vec<u64>,
+    pub public_credits_role_ids: Vec<u64>,
+    /// Needed to shut down agents summoned by someone else
+    pub shutdown_role_ids: Vec<u64>,
+}
+
+impl AgentPermissionSettings {
+    pub fn allows_channel(&self, channel_id: u64, parent_id: Option<u64>) -> bool {
+        let ids = [Some(channel_id), parent_id];
+        let is_in = |list: &[u64]| ids.iter().flatten().any(|id| list.contains(id));
+
+        if is_in(&self.denied_channel_ids) {
+            return false;
+        }
+
+        self.allowed_channel_ids.is_empty() || is_in(&self.allowed_channel_ids)
+    }
+
+    /// Whether a member with these roles meets a role requirement
+    pub fn meets_role_requirement(required_role_ids: &[u64], member_role_ids: &[u64]) -> bool {
+        required_role_ids.is_empty()
+            || required_role_ids
+                .iter()
+                .any(|role_id| member_role_ids.contains(role_id))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum GuildLanguage {
+    Portuguese,
+    English,
+    Spanish,
+}
+
+impl GuildLanguage {
+    pub const ALL: [GuildLanguage; 3] = [Self::Portuguese, Self::English, Self::Spanish];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Portuguese => "Português",
+            Self::English => "English",
+            Self::Spanish => "Español",
+        }
+    }
+
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::Portuguese => "pt-BR",
+            Self::English => "en-US",
+            Self::Spanish => "es-ES",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum NsfwPolicy {
+    Forbidden,
+    NsfwChannelsOnly,
+    Allowed,
+}
+
+impl NsfwPolicy {
+    pub const ALL: [NsfwPolicy; 3] = [Self::Forbidden, Self::NsfwChannelsOnly, Self::Allowed];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Forbidden => "Proibido",
+            Self::NsfwChannelsOnly => "Apenas em canais NSFW",
+            Self::Allowed => "Permitido",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct GuildSettings {
+    pub max_agents_per_channel: u32,
+    /// Agents leave after this long without new messages
+    pub inactivity_minutes: i64,
+    /// Messages starting with one of these are never seen by the agents
+    pub ignored_prefixes: Vec<String>,
+    /// Timezone of the dates shown to the agents
+    pub utc_offset_minutes: i32,
+    /// Language the agents should talk in
+    pub language: GuildLanguage,
+    /// Brain used when the summoner doesn't choose one
+    pub default_brain: InstanceBrain,
+    pub nsfw_policy: NsfwPolicy,
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        Self {
+            max_agents_per_channel: 3,
+            inactivity_minutes: 8,
+            ignored_prefixes: vec![">".to_string(), "_".to_string()],
+            utc_offset_minutes: 0,
+            language: GuildLanguage::Portuguese,
+            default_brain: InstanceBrain::GeminiFlash,
+            nsfw_policy: NsfwPolicy::NsfwChannelsOnly,
+        }
+    }
+}
+
+impl GuildSettings {
+    pub fn is_ignored_message(&self, content: &str) -> bool {
+        self.ignored_prefixes
+            .iter()
+            .any(|prefix| !prefix.is_empty() && content.starts_with(prefix.as_str()))
+    }
+
+    /// Formats a date in the guild timezone, the way agents read it in the history
+    pub fn format_date(&self, date: DateTime<Utc>) -> String {
+        let offset = FixedOffset::east_opt(self.utc_offset_minutes * 60)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        date.with_timezone(&offset)
+            .format("%d-%m-%Y %H:%M:%S")
+            .to_string()
+    }
+
+    pub fn format_utc_offset(&self) -> String {
+        let sign = if self.utc_offset_minutes < 0 {
+            '-'
+        } else {
+            '+'
+        };
+        let minutes = self.utc_offset_minutes.abs();
+        format!("UTC{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+
+    pub fn allows_nsfw(&self, is_nsfw_channel: bool) -> bool {
+        match self.nsfw_policy {
+            NsfwPolicy::Forbidden => false,
+            NsfwPolicy::NsfwChannelsOnly => is_nsfw_channel,
+            NsfwPolicy::Allowed => true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct GuildModel {
     #[serde(rename = "_id")]
@@ -19,6 +209,14 @@ pub struct GuildModel {
 
     #[serde(default = "HashSet::new")]
     pub flags: HashSet<GuildFlag>,
+    #[serde(default = "Default::default")]
+    pub output_safety: OutputSafetySettings,
+    #[serde(default = "Default::default")]
+    pub flood_guard: FloodGuardSettings,
+    #[serde(default = "Default::default")]
+    pub settings: GuildSettings,
+    #[serde(default = "Default::default")]
+    pub agent_permissions: AgentPermissionSettings,
 }
 
 impl GuildModel {
@@ -29,6 +227,10 @@ impl GuildModel {
             credits: 0,
             public_credits: 0,
             flags: HashSet::new(),
+            output_safety: OutputSafetySettings::default(),
+            flood_guard: FloodGuardSettings::default(),
+            settings: GuildSettings::default(),
+            agent_permissions: AgentPermissionSettings::default(),
         }
     }
 
@@ -6,7 +6,11 @@ use once_cell::sync::Lazy;
 use tokio_stream::StreamExt;
 use zenis_common::Cache;
 
-use crate::{common::query_by_id, instance_model::InstanceModel, ZenisDatabase};
+use crate::{
+    common::{query_by_id, query_user_chatted_with},
+    instance_model::InstanceModel,
+    ZenisDatabase,
+};
 
 static CACHE_ID: Lazy<Cache<ObjectId, InstanceModel>> = Lazy::new(|| Cache::new(1000));
 
@@ -77,6 +81,20 @@ impl InstanceCommands {
             .await?)
     }
 
+    pub async fn all_actives_in_guild(&self, guild_id: u64) -> anyhow::Result<Vec<InstanceModel>> {
+        let query = doc! {
+            "guild_id": guild_id as i64,
+            "active": true,
+        };
+
+        Ok(self
+            .collection
+            .find(query)
+            .await?
+            .collect::<Result<Vec<_>, _>>()
+            .await?)
+    }
+
     pub async fn all_actives(&self) -> anyhow::Result<Vec<InstanceModel>> {
         Ok(self
             .collection
@@ -117,4 +135,17 @@ impl InstanceCommands {
 
         Ok(())
     }
+
+    /// Whether the user summoned or talked to the agent in one of these conversations
+    pub async fn has_user_chatted_with(
+        &self,
+        user_id: u64,
+        agent_identifier: &str,
+    ) -> anyhow::Result<bool> {
+        Ok(self
+            .collection
+            .count_documents(query_user_chatted_with(user_id, agent_identifier))
+            .await?
+            > 0)
+    }
 }
@@ -1,6 +1,7 @@
 use bson::oid::ObjectId;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use zenis_common::clear_string;
 
 use crate::agent_model::{AgentModel, AgentPricing};
 
@@ -13,6 +14,13 @@ pub enum InstanceBrain {
 }
 
 impl InstanceBrain {
+    pub const ALL: [InstanceBrain; 4] = [
+        Self::GeminiFlash,
+        Self::GeminiPro,
+        Self::ClaudeHaiku,
+        Self::ZenisFinetuned,
+    ];
+
     pub fn extra_price_per_reply(&self) -> i64 {
         match self {
             Self::GeminiFlash => 0,
@@ -22,6 +30,11 @@ impl InstanceBrain {
         }
     }
 
+    /// What a reply of an agent with `pricing` costs with this brain
+    pub fn price_per_reply(&self, pricing: &AgentPricing) -> i64 {
+        pricing.price_per_reply + self.extra_price_per_reply()
+    }
+
     pub const fn name(&self) -> &'static str {
         match self {
             Self::GeminiFlash => "Gemini 2.5 Flash",
@@ -44,6 +57,64 @@ pub struct InstanceMessage {
     pub is_assistant: bool,
     pub text: String,
     pub image_url: Option<String>,
+    /// Discord message of an agent reply, so a moderator deleting it also removes it from memory
+    #[serde(default = "Default::default")]
+    pub message_id: Option<u64>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SpendingCapPeriod {
+    Session,
+    Hour,
+}
+
+/// Most credits an instance may spend on replies, chosen by the summoner
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SpendingCap {
+    pub max_credits: i64,
+    pub period: SpendingCapPeriod,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SpendingRecord {
+    pub timestamp: i64,
+    pub credits: i64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct InstanceSettings {
+    /// Only reply when the agent is mentioned, called by name or replied to
+    pub mention_only: bool,
+    /// Chance (0-100) of considering a message worth answering
+    pub reply_probability: u8,
+    /// Chance (0-100) of answering another agent or bot, so agents don't talk to each other forever
+    #[serde(default = "default_bot_reply_probability")]
+    pub bot_reply_probability: u8,
+    pub min_delay_secs: i64,
+    pub max_replies_per_minute: Option<u32>,
+    /// Remove roleplay actions like `*sorri*` from the replies before sending them
+    #[serde(default = "Default::default")]
+    pub strip_italic_actions: bool,
+    #[serde(default = "Default::default")]
+    pub spending_cap: Option<SpendingCap>,
+}
+
+fn default_bot_reply_probability() -> u8 {
+    70
+}
+
+impl Default for InstanceSettings {
+    fn default() -> Self {
+        Self {
+            mention_only: false,
+            reply_probability: 100,
+            bot_reply_probability: default_bot_reply_probability(),
+            min_delay_secs: 7,
+            max_replies_per_minute: None,
+            strip_italic_actions: false,
+            spending_cap: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -52,9 +123,14 @@ pub struct InstanceModel {
     pub id: ObjectId,
     pub summoner_id: u64,
     pub channel_id: u64,
+    #[serde(default = "Default::default")]
+    pub guild_id: Option<u64>,
     pub agent_identifier: String,
     pub agent_name: String,
     pub agent_description: String,
+    /// Sent as the avatar of every message, since the channel webhook is shared between agents
+    #[serde(default = "Default::default")]
+    pub agent_url_image: Option<String>,
     pub system_prompt: String,
     pub pricing: AgentPricing,
     pub brain: InstanceBrain,
@@ -74,6 +150,23 @@ pub struct InstanceModel {
     pub is_awaiting_new_messages: bool,
     #[serde(default = "Default::default")]
     pub error_counter: u32,
+
+    #[serde(default = "Default::default")]
+    pub settings: InstanceSettings,
+    #[serde(default = "Default::default")]
+    pub recent_reply_timestamps: Vec<i64>,
+    /// The saved session this instance continues, if it was resumed with /continuar
+    #[serde(default = "Default::default")]
+    pub session_id: Option<ObjectId>,
+
+    /// Credits charged for replies in this instance
+    #[serde(default = "Default::default")]
+    pub spent_credits: i64,
+    /// Charges of the last hour, for hourly spending caps
+    #[serde(default = "Default::default")]
+    pub recent_spendings: Vec<SpendingRecord>,
+    #[serde(default = "Default::default")]
+    pub spending_warning_sent: bool,
 }
 
 impl InstanceModel {
@@ -90,11 +183,13 @@ impl InstanceModel {
             id: ObjectId::new(),
             channel_id,
             summoner_id,
+            guild_id: None,
             pricing,
             brain: agent_brain,
             agent_identifier: agent_model.identifier.clone(),
             agent_name: agent_model.name.clone(),
             agent_description: agent_model.description.clone(),
+            agent_url_image: agent_model.agent_url_image.clone(),
             system_prompt,
 
             webhook_id,
@@ -111,9 +206,156 @@ impl InstanceModel {
             already_introduced: false,
             is_awaiting_new_messages: true,
             error_counter: 0,
+
+            settings: InstanceSettings::default(),
+            recent_reply_timestamps: vec![],
+            session_id: None,
+
+            spent_credits: 0,
+            recent_spendings: vec![],
+            spending_warning_sent: false,
         }
     }
 
+    pub fn with_settings(mut self, settings: InstanceSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Whether a message is directed at this agent: a reply to one of its messages, or its name written
+    /// in the content. The webhook is shared by every agent in the channel, so mentions of it can't tell them apart
+    pub fn is_addressed_by(&self, content: &str, replied_message_id: Option<u64>) -> bool {
+        replied_message_id.is_some_and(|replied_message_id| {
+            self.history.iter().any(|message| {
+                message.is_assistant && message.message_id == Some(replied_message_id)
+            })
+        }) || contains_name(content, &self.agent_name)
+    }
+
+    /// Users that talked in the conversation, plus the summoner
+    pub fn participant_ids(&self) -> Vec<u64> {
+        let mut ids = vec![self.summoner_id];
+        for message in self.history.iter().filter(|m| !m.is_assistant) {
+            // Consecutive user messages are merged, so each block has its own author
+            let block_ids = message
+                .text
+                .split("<!user_id/>")
+                .skip(1)
+                .filter_map(|block| {
+                    block
+                        .chars()
+                        .take_while(|c| c.is_ascii_digit())
+                        .collect::<String>()
+                        .parse::<u64>()
+                        .ok()
+                });
+
+            for id in block_ids.chain([message.user_id]) {
+                if id != 0 && !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+
+        ids
+    }
+
+    /// Whether the agent should be woken up for a new message, according to its settings
+    pub fn wants_to_reply(&self, is_addressed: bool, from_bot: bool, roll: u8) -> bool {
+        if self.settings.mention_only && !is_addressed {
+            return false;
+        }
+
+        let probability = if from_bot {
+            self.settings.bot_reply_probability
+        } else {
+            self.settings.reply_probability
+        };
+
+        // Bots naming each other would otherwise reply forever
+        (is_addressed && !from_bot) || roll < probability
+    }
+
+    pub fn register_spending(&mut self, credits: i64, now: i64) {
+        self.spent_credits += credits;
+        self.recent_spendings
+            .retain(|record| now - record.timestamp < 3600);
+        self.recent_spendings.push(SpendingRecord {
+            timestamp: now,
+            credits,
+        });
+    }
+
+    /// Credits that count against the spending cap right now: the whole session, or the last hour
+    pub fn capped_spending(&self, now: i64) -> Option<(i64, SpendingCap)> {
+        let cap = self.settings.spending_cap?;
+        let spent = match cap.period {
+            SpendingCapPeriod::Session => self.spent_credits,
+            SpendingCapPeriod::Hour => self
+                .recent_spendings
+                .iter()
+                .filter(|record| now - record.timestamp < 3600)
+                .map(|record| record.credits)
+                .sum(),
+        };
+
+        Some((spent, cap))
+    }
+
+    /// Whether paying for one more reply would go over the spending cap
+    pub fn would_exceed_spending_cap(&self, price: i64, now: i64) -> bool {
+        self.capped_spending(now)
+            .is_some_and(|(spent, cap)| spent + price > cap.max_credits)
+    }
+
+    /// Appends a message to the last history block, if that block was sent by `user_id`. It keeps its own
+    /// `<!message_id/>`, so it can still be edited or deleted. Returns false (and changes nothing) otherwise
+    pub fn append_to_last_block(&mut self, user_id: u64, message_id: u64, text: &str) -> bool {
+        let Some(last_message) = self.history.last_mut() else {
+            return false;
+        };
+
+        if last_message.is_assistant {
+            return false;
+        }
+
+        let Some(last_block) = split_message_blocks(&last_message.text).last().copied() else {
+            return false;
+        };
+
+        let block_user_id = last_block
+            .split("<!user_id/>")
+            .nth(1)
+            .and_then(|id| id.lines().next())
+            .and_then(|id| id.trim().parse::<u64>().ok());
+        if block_user_id != Some(user_id) || last_block.contains("<!system_instruction/>") {
+            return false;
+        }
+
+        last_message
+            .text
+            .push_str(&format!("\n<!message_id/>{message_id}\n<!message/>{text}"));
+        true
+    }
+
+    pub fn is_rate_limited(&self, now: i64) -> bool {
+        let Some(max_replies) = self.settings.max_replies_per_minute else {
+            return false;
+        };
+
+        self.recent_reply_timestamps
+            .iter()
+            .filter(|timestamp| now - **timestamp < 60)
+            .count()
+            >= max_replies as usize
+    }
+
+    pub fn register_reply(&mut self, now: i64) {
+        self.recent_reply_timestamps
+            .retain(|timestamp| now - *timestamp < 60);
+        self.recent_reply_timestamps.push(now);
+    }
+
     pub fn push_message(&mut self, message: InstanceMessage) {
         let instance_message: InstanceMessage = message.into();
 
@@ -148,6 +390,136 @@ impl InstanceModel {
         self.last_received_message_timestamp = Utc::now().timestamp();
     }
 
+    pub fn set_last_reply_message_id(&mut self, message_id: u64) {
+        if let Some(last_message) = self.history.last_mut() {
+            if last_message.is_assistant {
+                last_message.message_id = Some(message_id);
+            }
+        }
+    }
+
+    /// Links a delivered reply to its history entry, which may not be the last one anymore
+    pub fn set_reply_message_id(&mut self, history_text: &str, message_id: u64) {
+        if let Some(message) = self
+            .history
+            .iter_mut()
+            .rev()
+            .find(|m| m.is_assistant && m.message_id.is_none() && m.text == history_text)
+        {
+            message.message_id = Some(message_id);
+        }
+    }
+
+    /// Whether a user message is already in the history, even if merged into another block
+    pub fn contains_user_message(&self, message_id: u64) -> bool {
+        self.history
+            .iter()
+            .filter(|m| !m.is_assistant)
+            .flat_map(|m| split_message_blocks(&m.text))
+            .flat_map(|block| split_block_messages(block).1)
+            .any(|part| block_message_id(part) == Some(message_id))
+    }
+
+    /// Replaces the content of a user message in the history. Returns whether it was found
+    pub fn edit_user_message(&mut self, message_id: u64, new_content: &str) -> bool {
+        let mut found = false;
+        for message in self.history.iter_mut().filter(|m| !m.is_assistant) {
+            let blocks = split_message_blocks(&message.text)
+                .into_iter()
+                .map(|block| {
+                    let (header, messages) = split_block_messages(block);
+                    let messages = messages
+                        .into_iter()
+                        .map(|part| {
+                            if block_message_id(part) != Some(message_id) {
+                                return part.to_owned();
+                            }
+
+                            let Some(index) = part.find("<!message/>") else {
+                                return part.to_owned();
+                            };
+
+                            found = true;
+                            let (part_header, tail) = part.split_at(index + "<!message/>".len());
+                            let suffix = match tail.find("<!system_instruction/>") {
+                                Some(index) => &tail[index..],
+                                None if tail.ends_with('\n') => "\n",
+                                None => "",
+                            };
+                            let separator = if suffix.starts_with('<') { "\n" } else { "" };
+
+                            format!("{part_header}{new_content}{separator}{suffix}")
+                        })
+                        .collect::<String>();
+
+                    format!("{header}{messages}")
+                })
+                .collect::<String>();
+
+            message.text = blocks;
+        }
+
+        found
+    }
+
+    /// Removes deleted messages (user blocks or agent replies) from the history. Returns whether anything changed
+    pub fn remove_messages(&mut self, message_ids: &[u64]) -> bool {
+        let mut changed = false;
+        let mut history: Vec<InstanceMessage> = Vec::with_capacity(self.history.len());
+
+        for mut message in std::mem::take(&mut self.history) {
+            if message.is_assistant {
+                if message
+                    .message_id
+                    .is_some_and(|id| message_ids.contains(&id))
+                {
+                    changed = true;
+                    continue;
+                }
+            } else {
+                let kept = split_message_blocks(&message.text)
+                    .into_iter()
+                    .filter_map(|block| {
+                        let (header, messages) = split_block_messages(block);
+                        let kept_messages = messages
+                            .iter()
+                            .filter(|part| {
+                                !block_message_id(part).is_some_and(|id| message_ids.contains(&id))
+                            })
+                            .copied()
+                            .collect::<Vec<_>>();
+
+                        // A block goes away with its last message
+                        match kept_messages.len() {
+                            _ if messages.is_empty() => Some(block.to_owned()),
+                            0 => None,
+                            _ => Some(format!("{header}{}", kept_messages.concat())),
+                        }
+                    })
+                    .collect::<String>();
+
+                if kept.len() != message.text.len() {
+                    changed = true;
+                    message.text = kept.trim_end().to_owned();
+                    if message.text.is_empty() {
+                        continue;
+                    }
+                }
+            }
+
+            // Keep user and agent turns alternating after a removal
+            match history.last_mut() {
+                Some(last) if !message.is_assistant && !last.is_assistant => {
+                    last.text.push_str(&format!("\n{}", message.text));
+                }
+                _ => history.push(message),
+            }
+        }
+
+        self.history = history;
+        changed
+    }
+
     pub fn increment_error(&mut self) {
         self.error_counter += 1;
         if self.error_counter > 10 {
@@ -164,6 +536,7 @@ impl InstanceModel {
                 user_id: 0,
                 text: format!("<!system_instruction/>Se apresente, {}.", self.agent_name),
                 image_url: None,
+                message_id: None,
             });
         }
 
@@ -172,6 +545,7 @@ impl InstanceModel {
             user_id: self.webhook_id,
             text: format!("<!message/>{}", introduction_message.to_string()),
             image_url: None,
+            message_id: None,
         };
 
         self.push_message(introduction_message.clone());
@@ -179,3 +553,199 @@ impl InstanceModel {
         introduction_message
     }
 }
+
+/// Whether `name` is written as whole words in `content`, ignoring case and accents, so "Ana" doesn't match "banana"
+fn contains_name(content: &str, name: &str) -> bool {
+    let words = |text: &str| {
+        clear_string(text)
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+
+    let name_words = words(name);
+    !name_words.is_empty()
+        && words(content)
+            .windows(name_words.len())
+            .any(|window| window == name_words.as_slice())
+}
+
+/// Splits a (possibly merged) user history entry into its `<!name/>` blocks, keeping any leading text as its own block
+fn split_message_blocks(text: &str) -> Vec<&str> {
+    let mut indices = text
+        .match_indices("<!name/>")
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if indices.first() != Some(&0) {
+        indices.insert(0, 0);
+    }
+
+    indices
+        .iter()
+        .enumerate()
+        .map(|(i, start)| {
+            let end = indices.get(i + 1).copied().unwrap_or(text.len());
+            &text[*start..end]
+        })
+        .collect()
+}
+
+/// Splits a block into its header and one part per message, starting at each `<!message_id/>`.
+/// Rapid-fire messages are merged into the block of their author, so a block may have several
+fn split_block_messages(block: &str) -> (&str, Vec<&str>) {
+    let indices = block
+        .match_indices("<!message_id/>")
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let Some(first) = indices.first() else {
+        return (block, vec![]);
+    };
+
+    let messages = indices
+        .iter()
+        .enumerate()
+        .map(|(i, start)| {
+            let end = indices.get(i + 1).copied().unwrap_or(block.len());
+            &block[*start..end]
+        })
+        .collect();
+
+    (&block[..*first], messages)
+}
+
+fn block_message_id(block: &str) -> Option<u64> {
+    let start = block.find("<!message_id/>")? + "<!message_id/>".len();
+    block[start..]
+        .lines()
+        .next()
+        .and_then(|id| id.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_instance() -> InstanceModel {
+        let agent = AgentModel::new(
+            1,
+            "teste",
+            "Teste",
+            "Um agente",
+            "Oi!",
+            AgentPricing::default(),
+        );
+        InstanceModel::new(
+            InstanceBrain::GeminiFlash,
+            (100, 1),
+            agent,
+            AgentPricing::default(),
+            (200, "token".to_string()),
+            CreditsPaymentMethod::UserCredits(1),
+            String::new(),
+        )
+    }
+
+    fn user_block(user_id: u64, message_id: u64, content: &str) -> InstanceMessage {
+        InstanceMessage {
+            is_assistant: false,
+            user_id,
+            text: format!(
+                "<!name/>Usuário\n<!user/>@usuario\n<!user_id/>{user_id}\n<!date/>hoje\n<!message_id/>{message_id}\n<!channel/>#geral\n<!channel_id/>100\n<!message/>{content}"
+            ),
+            image_url: None,
+            message_id: None,
+        }
+    }
+
+    #[test]
+    fn merged_messages_keep_their_own_id() {
+        let mut instance = make_instance();
+        instance.push_message(user_block(5, 1, "primeira"));
+
+        assert!(instance.append_to_last_block(5, 2, "segunda"));
+        assert!(!instance.append_to_last_block(6, 3, "de outro usuário"));
+
+        let text = &instance.history.last().unwrap().text;
+        assert!(text.ends_with("<!message/>primeira\n<!message_id/>2\n<!message/>segunda"));
+        assert!(!text.contains("de outro usuário"));
+    }
+
+    #[test]
+    fn merged_messages_can_be_edited() {
+        let mut instance = make_instance();
+        instance.push_message(user_block(5, 1, "primeira"));
+        instance.append_to_last_block(5, 2, "segunda");
+        instance.append_to_last_block(5, 3, "terceira");
+
+        assert!(instance.edit_user_message(2, "editada"));
+        assert!(instance.edit_user_message(1, "primeira editada"));
+        assert!(!instance.edit_user_message(4, "inexistente"));
+
+        let text = &instance.history.last().unwrap().text;
+        assert!(text.ends_with(
+            "<!message/>primeira editada\n<!message_id/>2\n<!message/>editada\n<!message_id/>3\n<!message/>terceira"
+        ));
+    }
+
+    #[test]
+    fn merged_messages_can_be_removed() {
+        let mut instance = make_instance();
+        instance.push_message(user_block(5, 1, "primeira"));
+        instance.append_to_last_block(5, 2, "segunda");
+        instance.append_to_last_block(5, 3, "terceira");
+
+        assert!(instance.remove_messages(&[2]));
+        let text = instance.history.last().unwrap().text.clone();
+        assert!(text.contains("<!user_id/>5"));
+        assert!(text.contains("primeira"));
+        assert!(!text.contains("segunda"));
+        assert!(text.ends_with("<!message_id/>3\n<!message/>terceira"));
+
+        // The header stays while the block still has a message
+        assert!(instance.remove_messages(&[1]));
+        let text = instance.history.last().unwrap().text.clone();
+        assert!(text.starts_with("<!name/>Usuário"));
+        assert!(!text.contains("primeira"));
+        assert!(text.contains("terceira"));
+
+        assert!(instance.remove_messages(&[3]));
+        assert!(instance.history.is_empty());
+        assert!(!instance.remove_messages(&[3]));
+    }
+
+    #[test]
+    fn merged_messages_are_found_by_id() {
+        let mut instance = make_instance();
+        instance.push_message(user_block(5, 1, "primeira"));
+        instance.append_to_last_block(5, 2, "segunda");
+
+        assert!(instance.contains_user_message(1));
+        assert!(instance.contains_user_message(2));
+        assert!(!instance.contains_user_message(3));
+    }
+
+    #[test]
+    fn name_is_matched_as_whole_words() {
+        let mut instance = make_instance();
+        instance.agent_name = String::from("Ana Júlia");
+
+        assert!(instance.is_addressed_by("oi, ana julia!", None));
+        assert!(instance.is_addressed_by("ANA JÚLIA, tudo bem?", None));
+        assert!(!instance.is_addressed_by("comi uma banana julia", None));
+        assert!(!instance.is_addressed_by("ana", None));
+    }
+
+    #[test]
+    fn bots_need_the_probability_even_when_addressed() {
+        let mut instance = make_instance();
+        instance.settings.bot_reply_probability = 30;
+
+        assert!(instance.wants_to_reply(true, false, 99));
+        assert!(!instance.wants_to_reply(true, true, 50));
+        assert!(instance.wants_to_reply(true, true, 10));
+
+        instance.settings.mention_only = true;
+        assert!(!instance.wants_to_reply(false, true, 10));
+    }
+}
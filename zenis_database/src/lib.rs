@@ -1,10 +1,24 @@
 pub mod agent_commands;
 pub mod agent_model;
+pub mod agent_version_commands;
+pub mod agent_version_model;
+pub mod archived_instance_commands;
+pub mod archived_instance_model;
+pub mod channel_webhook_commands;
+pub mod channel_webhook_model;
 pub mod common;
+pub mod earning_commands;
+pub mod earning_model;
 pub mod guild_commands;
 pub mod guild_model;
 pub mod instance_commands;
 pub mod instance_model;
+pub mod outbox_commands;
+pub mod outbox_model;
+pub mod review_commands;
+pub mod review_model;
+pub mod session_commands;
+pub mod session_model;
 pub mod transaction;
 pub mod user_commands;
 pub mod user_model;
@@ -13,12 +27,26 @@ use std::sync::Arc;
 
 use agent_commands::AgentCommands;
 use agent_model::AgentModel;
+use agent_version_commands::AgentVersionCommands;
+use agent_version_model::AgentVersionModel;
+use archived_instance_commands::ArchivedInstanceCommands;
+use archived_instance_model::ArchivedInstanceModel;
 use bson::doc;
+use channel_webhook_commands::ChannelWebhookCommands;
+use channel_webhook_model::ChannelWebhookModel;
+use earning_commands::EarningCommands;
+use earning_model::EarningModel;
 use guild_commands::GuildCommands;
 use guild_model::GuildModel;
 use instance_commands::InstanceCommands;
 use instance_model::InstanceModel;
-use mongodb::{Client, Collection, Database, IndexModel};
+use mongodb::{options::IndexOptions, Client, Collection, Database, IndexModel};
+use outbox_commands::OutboxCommands;
+use outbox_model::OutboxMessageModel;
+use review_commands::ReviewCommands;
+use review_model::ReviewModel;
+use session_commands::SessionCommands;
+use session_model::SessionModel;
 
 pub use mongodb::bson;
 pub use mongodb::error::Error as MongoDBError;
@@ -76,12 +104,94 @@ impl ZenisDatabase {
             .await
             .unwrap();
 
+        // AGENT VERSION INDEXES
+        let agent_versions: Collection<AgentVersionModel> = self.db().collection("agent_versions");
+        agent_versions
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "agent_identifier": 1, "number": -1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+            .unwrap();
+
         // INSTANCE INDEXES
         let instances: Collection<InstanceModel> = self.db().collection("instances");
         instances
             .create_index(IndexModel::builder().keys(doc! { "channel_id": 1 }).build())
             .await
             .unwrap();
+
+        // ARCHIVED INSTANCE INDEXES
+        let archived_instances: Collection<ArchivedInstanceModel> =
+            self.db().collection("archived_instances");
+        archived_instances
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "channel_id": 1, "archived_at_timestamp": -1 })
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        // CHANNEL WEBHOOK INDEXES
+        let channel_webhooks: Collection<ChannelWebhookModel> =
+            self.db().collection("channel_webhooks");
+        channel_webhooks
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "channel_id": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        // OUTBOX INDEXES
+        let outbox: Collection<OutboxMessageModel> = self.db().collection("outbox");
+        outbox
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "created_at_timestamp": 1 })
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        // SESSION INDEXES
+        let sessions: Collection<SessionModel> = self.db().collection("sessions");
+        sessions
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "owner_id": 1, "updated_at_timestamp": -1 })
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        // REVIEW INDEXES
+        let reviews: Collection<ReviewModel> = self.db().collection("reviews");
+        reviews
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "agent_identifier": 1, "user_id": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        // EARNING INDEXES
+        let earnings: Collection<EarningModel> = self.db().collection("earnings");
+        earnings
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "creator_user_id": 1, "created_at_timestamp": -1 })
+                    .build(),
+            )
+            .await
+            .unwrap();
     }
 
     pub fn db(&self) -> Database {
@@ -106,13 +216,48 @@ impl ZenisDatabase {
         AgentCommands::new(collection, self.clone())
     }
 
+    pub fn agent_versions(&self) -> AgentVersionCommands {
+        let collection = self.db().collection("agent_versions");
+        AgentVersionCommands::new(collection, self.clone())
+    }
+
     pub fn instances(&self) -> InstanceCommands {
         let collection = self.db().collection("instances");
         InstanceCommands::new(collection, self.clone())
     }
 
+    pub fn archived_instances(&self) -> ArchivedInstanceCommands {
+        let collection = self.db().collection("archived_instances");
+        ArchivedInstanceCommands::new(collection, self.clone())
+    }
+
+    pub fn channel_webhooks(&self) -> ChannelWebhookCommands {
+        let collection = self.db().collection("channel_webhooks");
+        ChannelWebhookCommands::new(collection, self.clone())
+    }
+
+    pub fn outbox(&self) -> OutboxCommands {
+        let collection = self.db().collection("outbox");
+        OutboxCommands::new(collection, self.clone())
+    }
+
+    pub fn sessions(&self) -> SessionCommands {
+        let collection = self.db().collection("sessions");
+        SessionCommands::new(collection, self.clone())
+    }
+
     pub fn transactions(&self) -> TransactionCommands {
         let collection = self.db().collection("transactions");
         TransactionCommands::new(collection, self.clone())
     }
+
+    pub fn reviews(&self) -> ReviewCommands {
+        let collection = self.db().collection("reviews");
+        ReviewCommands::new(collection, self.clone())
+    }
+
+    pub fn earnings(&self) -> EarningCommands {
+        let collection = self.db().collection("earnings");
+        EarningCommands::new(collection, self.clone())
+    }
 }
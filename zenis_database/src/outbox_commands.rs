@@ -0,0 +1,45 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::Collection;
+use tokio_stream::StreamExt;
+
+use crate::{common::query_by_id, outbox_model::OutboxMessageModel, ZenisDatabase};
+
+#[allow(unused)]
+pub struct OutboxCommands {
+    pub collection: Collection<OutboxMessageModel>,
+    db: ZenisDatabase,
+}
+
+impl OutboxCommands {
+    pub const fn new(collection: Collection<OutboxMessageModel>, db: ZenisDatabase) -> Self {
+        Self { collection, db }
+    }
+
+    pub async fn save(&self, message: OutboxMessageModel) -> anyhow::Result<()> {
+        self.collection
+            .replace_one(query_by_id(message.id), &message)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_message(&self, message: OutboxMessageModel) -> anyhow::Result<()> {
+        self.collection.insert_one(message).await?;
+        Ok(())
+    }
+
+    /// Every pending message, oldest first so each channel keeps its order
+    pub async fn all_pending(&self) -> anyhow::Result<Vec<OutboxMessageModel>> {
+        Ok(self
+            .collection
+            .find(doc! {})
+            .sort(doc! { "created_at_timestamp": 1 })
+            .await?
+            .collect::<Result<Vec<_>, _>>()
+            .await?)
+    }
+
+    pub async fn delete_message(&self, id: ObjectId) -> anyhow::Result<()> {
+        self.collection.delete_one(query_by_id(id)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use bson::oid::ObjectId;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::instance_model::InstanceModel;
+
+/// An agent reply waiting to be delivered. The reply is only charged once Discord confirms it was sent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboxMessageModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub instance_id: ObjectId,
+    pub channel_id: u64,
+    pub agent_identifier: String,
+    /// The raw assistant text in the history, to link the sent message back to it
+    pub history_text: String,
+    pub content: String,
+    pub image_processed: bool,
+
+    /// Long replies are split in several messages. A retry continues from the first undelivered one
+    #[serde(default = "Default::default")]
+    pub delivered_parts: u32,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub next_attempt_timestamp: i64,
+    pub created_at_timestamp: i64,
+}
+
+impl OutboxMessageModel {
+    pub fn new(
+        instance: &InstanceModel,
+        history_text: impl ToString,
+        content: impl ToString,
+        image_processed: bool,
+    ) -> Self {
+        let now = Utc::now().timestamp();
+        Self {
+            id: ObjectId::new(),
+            instance_id: instance.id,
+            channel_id: instance.channel_id,
+            agent_identifier: instance.agent_identifier.clone(),
+            history_text: history_text.to_string(),
+            content: content.to_string(),
+            image_processed,
+
+            delivered_parts: 0,
+            attempts: 0,
+            last_error: None,
+            next_attempt_timestamp: now,
+            created_at_timestamp: now,
+        }
+    }
+}
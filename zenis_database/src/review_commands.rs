@@ -0,0 +1,72 @@
+use bson::doc;
+use mongodb::Collection;
+use tokio_stream::StreamExt;
+
+use crate::{common::query_by_id, review_model::ReviewModel, ZenisDatabase};
+
+#[allow(unused)]
+pub struct ReviewCommands {
+    pub collection: Collection<ReviewModel>,
+    db: ZenisDatabase,
+}
+
+impl ReviewCommands {
+    pub const fn new(collection: Collection<ReviewModel>, db: ZenisDatabase) -> Self {
+        Self { collection, db }
+    }
+
+    pub async fn save(&self, review: ReviewModel) -> anyhow::Result<()> {
+        self.collection
+            .replace_one(query_by_id(review.id), &review)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_review(&self, review: ReviewModel) -> anyhow::Result<()> {
+        self.collection.insert_one(review).await?;
+        Ok(())
+    }
+
+    pub async fn get_by_agent_and_user(
+        &self,
+        agent_identifier: &str,
+        user_id: u64,
+    ) -> anyhow::Result<Option<ReviewModel>> {
+        Ok(self
+            .collection
+            .find_one(doc! {
+                "agent_identifier": agent_identifier,
+                "user_id": user_id as i64,
+            })
+            .await?)
+    }
+
+    /// The best commented review of each agent, preferring the most recent among equal ratings
+    pub async fn get_top_commented_by_agents(
+        &self,
+        agent_identifiers: &[String],
+    ) -> anyhow::Result<Vec<ReviewModel>> {
+        let reviews = self
+            .collection
+            .find(doc! {
+                "agent_identifier": { "$in": agent_identifiers },
+                "comment": { "$ne": null },
+            })
+            .sort(doc! { "rating": -1, "updated_at_timestamp": -1 })
+            .await?
+            .collect::<Result<Vec<_>, _>>()
+            .await?;
+
+        let mut top_reviews: Vec<ReviewModel> = vec![];
+        for review in reviews {
+            if !top_reviews
+                .iter()
+                .any(|r| r.agent_identifier == review.agent_identifier)
+            {
+                top_reviews.push(review);
+            }
+        }
+
+        Ok(top_reviews)
+    }
+}
@@ -0,0 +1,52 @@
+use bson::oid::ObjectId;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// A rating (1 to 5) left by a user who chatted with an agent. A user has at most one per agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub agent_identifier: String,
+    pub user_id: u64,
+    pub rating: u8,
+    pub comment: Option<String>,
+
+    pub created_at_timestamp: i64,
+    pub updated_at_timestamp: i64,
+}
+
+impl ReviewModel {
+    pub fn new(
+        agent_identifier: impl ToString,
+        user_id: u64,
+        rating: u8,
+        comment: Option<String>,
+    ) -> Self {
+        let now = Utc::now().timestamp();
+        Self {
+            id: ObjectId::new(),
+            agent_identifier: agent_identifier.to_string(),
+            user_id,
+            rating,
+            comment,
+
+            created_at_timestamp: now,
+            updated_at_timestamp: now,
+        }
+    }
+
+    pub fn update(&mut self, rating: u8, comment: Option<String>) {
+        self.rating = rating;
+        self.comment = comment;
+        self.updated_at_timestamp = Utc::now().timestamp();
+    }
+
+    pub fn stars(&self) -> String {
+        format!(
+            "{}{}",
+            "★".repeat(self.rating as usize),
+            "☆".repeat(5usize.saturating_sub(self.rating as usize))
+        )
+    }
+}
@@ -0,0 +1,83 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::Collection;
+use tokio_stream::StreamExt;
+
+use crate::{common::query_by_id, session_model::SessionModel, ZenisDatabase};
+
+#[allow(unused)]
+pub struct SessionCommands {
+    pub collection: Collection<SessionModel>,
+    db: ZenisDatabase,
+}
+
+impl SessionCommands {
+    pub const fn new(collection: Collection<SessionModel>, db: ZenisDatabase) -> Self {
+        Self { collection, db }
+    }
+
+    pub async fn save(&self, session: SessionModel) -> anyhow::Result<()> {
+        self.collection
+            .replace_one(query_by_id(session.id), &session)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_session(&self, session: SessionModel) -> anyhow::Result<()> {
+        self.collection.insert_one(session).await?;
+        Ok(())
+    }
+
+    /// Summaries are written after the session is saved, since making one takes a while
+    pub async fn set_summary(&self, id: ObjectId, summary: &str) -> anyhow::Result<()> {
+        self.collection
+            .update_one(query_by_id(id), doc! { "$set": { "summary": summary } })
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, id: ObjectId) -> anyhow::Result<Option<SessionModel>> {
+        Ok(self.collection.find_one(query_by_id(id)).await?)
+    }
+
+    /// Sessions of a user, most recently played first
+    pub async fn get_all_by_owner(&self, owner_id: u64) -> anyhow::Result<Vec<SessionModel>> {
+        Ok(self
+            .collection
+            .find(doc! { "owner_id": owner_id as i64 })
+            .sort(doc! { "updated_at_timestamp": -1 })
+            .await?
+            .collect::<Result<Vec<_>, _>>()
+            .await?)
+    }
+
+    pub async fn has_user_saved_with(
+        &self,
+        user_id: u64,
+        agent_identifier: &str,
+    ) -> anyhow::Result<bool> {
+        let query = doc! {
+            "owner_id": user_id as i64,
+            "agent.identifier": agent_identifier,
+        };
+
+        Ok(self.collection.count_documents(query).await? > 0)
+    }
+
+    pub async fn delete_session(&self, id: ObjectId) -> anyhow::Result<()> {
+        self.collection.delete_one(query_by_id(id)).await?;
+        Ok(())
+    }
+
+    pub async fn delete_oldest_over_limit(
+        &self,
+        owner_id: u64,
+        limit: usize,
+    ) -> anyhow::Result<()> {
+        let sessions = self.get_all_by_owner(owner_id).await?;
+        for session in sessions.into_iter().skip(limit) {
+            self.delete_session(session.id).await?;
+        }
+
+        Ok(())
+    }
+}
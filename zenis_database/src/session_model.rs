@@ -0,0 +1,108 @@
+use bson::oid::ObjectId;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent_model::{AgentExampleDialogue, AgentLineage, AgentLoreEntry, AgentModel, AgentPricing},
+    instance_model::{InstanceBrain, InstanceMessage, InstanceModel, InstanceSettings},
+};
+
+/// The agent as it was when the session was saved, so later edits don't change an ongoing story
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentSnapshot {
+    pub identifier: String,
+    pub creator_user_id: u64,
+    pub name: String,
+    pub description: String,
+    pub introduction_message: String,
+    pub agent_url_image: Option<String>,
+    pub pricing: AgentPricing,
+    #[serde(default = "Default::default")]
+    pub example_dialogues: Vec<AgentExampleDialogue>,
+    #[serde(default = "Default::default")]
+    pub lore: Vec<AgentLoreEntry>,
+    #[serde(default = "Default::default")]
+    pub forked_from: Option<AgentLineage>,
+}
+
+impl From<&AgentModel> for AgentSnapshot {
+    fn from(agent: &AgentModel) -> Self {
+        Self {
+            identifier: agent.identifier.clone(),
+            creator_user_id: agent.creator_user_id,
+            name: agent.name.clone(),
+            description: agent.description.clone(),
+            introduction_message: agent.introduction_message.clone(),
+            agent_url_image: agent.agent_url_image.clone(),
+            pricing: agent.pricing,
+            example_dialogues: agent.example_dialogues.clone(),
+            lore: agent.lore.clone(),
+            forked_from: agent.forked_from.clone(),
+        }
+    }
+}
+
+impl AgentSnapshot {
+    pub fn to_agent_model(&self) -> AgentModel {
+        let mut agent = AgentModel::new(
+            self.creator_user_id,
+            &self.identifier,
+            &self.name,
+            &self.description,
+            &self.introduction_message,
+            self.pricing,
+        );
+        agent.example_dialogues = self.example_dialogues.clone();
+        agent.lore = self.lore.clone();
+        agent.forked_from = self.forked_from.clone();
+
+        match &self.agent_url_image {
+            Some(url_image) => agent.with_url_image(url_image),
+            None => agent,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub owner_id: u64,
+    pub name: String,
+    pub agent: AgentSnapshot,
+    pub brain: InstanceBrain,
+    pub history: Vec<InstanceMessage>,
+    pub summary: String,
+    #[serde(default = "Default::default")]
+    pub settings: InstanceSettings,
+
+    pub created_at_timestamp: i64,
+    pub updated_at_timestamp: i64,
+}
+
+impl SessionModel {
+    pub fn new(instance: &InstanceModel, agent: &AgentModel, summary: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: ObjectId::new(),
+            owner_id: instance.summoner_id,
+            name: format!("{} ({})", agent.name, now.format("%d/%m %H:%M")),
+            agent: AgentSnapshot::from(agent),
+            brain: instance.brain,
+            history: instance.history.clone(),
+            summary,
+            settings: instance.settings,
+
+            created_at_timestamp: now.timestamp(),
+            updated_at_timestamp: now.timestamp(),
+        }
+    }
+
+    pub fn update_from_instance(&mut self, instance: &InstanceModel, summary: String) {
+        self.brain = instance.brain;
+        self.history = instance.history.clone();
+        self.summary = summary;
+        self.settings = instance.settings;
+        self.updated_at_timestamp = Utc::now().timestamp();
+    }
+}
@@ -31,6 +31,28 @@ impl UserCommands {
         Ok(())
     }
 
+    /// Atomically removes the credits only if the user has enough of them, returning if it succeeded
+    pub async fn try_remove_credits(
+        &self,
+        user_id: Id<UserMarker>,
+        quantity: i64,
+    ) -> anyhow::Result<bool> {
+        let updated = self
+            .collection
+            .find_one_and_update(
+                doc! { "user_id": user_id.get() as i64, "credits": { "$gte": quantity } },
+                doc! { "$inc": { "credits": -quantity } },
+            )
+            .await?;
+
+        let Some(user_data) = updated else {
+            return Ok(false);
+        };
+
+        self.remove_from_cache(&user_data);
+        Ok(true)
+    }
+
     pub fn remove_from_cache(&self, user_data: &UserModel) {
         CACHE_ID.remove(&user_data.id);
         CACHE_USER_ID.remove(&user_data.user_id);
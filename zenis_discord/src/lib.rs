@@ -2,6 +2,8 @@ mod component;
 mod embed;
 mod modal_builder;
 mod modal_response;
+mod renderer;
+mod safety;
 mod util;
 
 pub use twilight_gateway;
@@ -17,4 +19,6 @@ pub use component::*;
 pub use embed::*;
 pub use modal_builder::{Modal, ModalBuilder};
 pub use modal_response::ModalResponse;
+pub use renderer::*;
+pub use safety::*;
 pub use util::*;
@@ -0,0 +1,323 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use twilight_model::http::attachment::Attachment;
+
+pub const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+/// Inline markers closed when a message ends with one of them still open. Longer ones come first
+const INLINE_MARKERS: &[&str] = &["**", "__", "~~", "||", "`", "*", "_"];
+
+static ITALIC_ACTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"[_*][^_*]+[_*]").unwrap());
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RenderOptions {
+    /// Removes roleplay actions like `*sorri*` or `_acena_`
+    pub strip_italic_actions: bool,
+    pub max_message_length: usize,
+    /// Content that would need more messages than this is sent as a text file instead
+    pub max_messages: usize,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            strip_italic_actions: false,
+            max_message_length: DISCORD_MESSAGE_LIMIT,
+            max_messages: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RenderedMessage {
+    Text(String),
+    File {
+        preview: String,
+        file_name: String,
+        content: String,
+    },
+}
+
+impl RenderedMessage {
+    pub fn content(&self) -> &str {
+        match self {
+            Self::Text(text) => text,
+            Self::File { preview, .. } => preview,
+        }
+    }
+
+    pub fn attachments(&self) -> Vec<Attachment> {
+        match self {
+            Self::Text(_) => vec![],
+            Self::File {
+                file_name, content, ..
+            } => vec![Attachment::from_bytes(
+                file_name.clone(),
+                content.clone().into_bytes(),
+                0,
+            )],
+        }
+    }
+}
+
+/// Turns an agent reply into the messages that should be sent, in order
+pub fn render_message(content: &str, options: RenderOptions) -> Vec<RenderedMessage> {
+    let content = if options.strip_italic_actions {
+        strip_italic_actions(content)
+    } else {
+        content.trim().to_owned()
+    };
+
+    if content.is_empty() {
+        return vec![];
+    }
+
+    let parts = split_message(&content, options.max_message_length);
+    if parts.len() > options.max_messages.max(1) {
+        let preview = content.chars().take(300).collect::<String>();
+        return vec![RenderedMessage::File {
+            preview: format!(
+                "{}…\n-# A mensagem completa está no arquivo.",
+                close_unbalanced_markdown(preview.trim_end())
+            ),
+            file_name: "mensagem.txt".to_string(),
+            content,
+        }];
+    }
+
+    parts.into_iter().map(RenderedMessage::Text).collect()
+}
+
+pub fn strip_italic_actions(input: &str) -> String {
+    let output = ITALIC_ACTION.replace_all(input, "");
+    output.trim().to_string()
+}
+
+/// Splits a text into parts of at most `limit` characters, preferring code block, paragraph,
+/// line and sentence boundaries. A code block cut in half is closed and reopened in the next part
+pub fn split_message(content: &str, limit: usize) -> Vec<String> {
+    // Room for closing and reopening a code block fence
+    const FENCE_RESERVE: usize = 24;
+    let limit = limit.max(FENCE_RESERVE * 2);
+
+    let mut parts = vec![];
+    let mut reopen_fence: Option<String> = None;
+    let mut remaining = content.trim().to_owned();
+
+    while !remaining.is_empty() {
+        if let Some(fence) = reopen_fence.take() {
+            remaining = format!("{fence}\n{remaining}");
+        }
+
+        // The last part also needs room for the closers it may get
+        if remaining.chars().count() <= limit - FENCE_RESERVE {
+            parts.push(close_unbalanced_markdown(&remaining));
+            break;
+        }
+
+        let window = remaining
+            .char_indices()
+            .nth(limit - FENCE_RESERVE)
+            .map(|(index, _)| index)
+            .unwrap_or(remaining.len());
+        let cut = find_split_point(&remaining[..window]);
+
+        let (part, rest) = remaining.split_at(cut);
+        let mut part = part.trim_end().to_owned();
+        if let Some(fence) = open_code_fence(&part) {
+            part.push_str("\n```");
+            reopen_fence = Some(fence);
+        }
+
+        parts.push(close_unbalanced_markdown(&part));
+        remaining = rest.trim_start().to_owned();
+    }
+
+    parts
+}
+
+fn find_split_point(window: &str) -> usize {
+    let min = window.len() / 3;
+
+    let code_block_end = window
+        .match_indices("```\n")
+        .map(|(index, _)| index + 3)
+        .filter(|index| *index > min && open_code_fence(&window[..*index]).is_none())
+        .last();
+
+    let boundaries = [
+        code_block_end,
+        window.rfind("\n\n").filter(|index| *index > min),
+        window.rfind('\n').filter(|index| *index > min),
+        ["? ", "! ", ". ", "… "]
+            .iter()
+            .filter_map(|end| window.rfind(end).map(|index| index + end.len() - 1))
+            .max()
+            .filter(|index| *index > min),
+        window.rfind(' ').filter(|index| *index > min),
+    ];
+
+    boundaries
+        .into_iter()
+        .flatten()
+        .next()
+        .unwrap_or(window.len())
+}
+
+/// The opening fence (with its language) of a code block that is still open at the end of the text
+fn open_code_fence(text: &str) -> Option<String> {
+    let mut open = None;
+    for line in text.lines() {
+        let line = line.trim_start();
+        if !line.starts_with("```") {
+            continue;
+        }
+
+        open = match open {
+            Some(_) => None,
+            // A whole code block in a single line
+            None if line[3..].contains("```") => None,
+            None => Some(line.trim().to_owned()),
+        };
+    }
+
+    open
+}
+
+/// Closes code blocks and inline markdown left open, so a cut doesn't break the rest of the chat
+pub fn close_unbalanced_markdown(text: &str) -> String {
+    let mut output = text.to_owned();
+    if open_code_fence(text).is_some() {
+        output.push_str("\n```");
+        return output;
+    }
+
+    // Inline markers inside code blocks don't count
+    let outside_code = text.split("```").step_by(2).collect::<Vec<_>>().join(" ");
+
+    let mut closing = String::new();
+    let mut scanned = outside_code;
+    for marker in INLINE_MARKERS {
+        if scanned.matches(marker).count() % 2 == 1 {
+            closing.insert_str(0, marker);
+        }
+
+        scanned = scanned.replace(marker, " ");
+    }
+
+    output.push_str(&closing);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_options(max_messages: usize) -> RenderOptions {
+        RenderOptions {
+            max_messages,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn short_message_is_kept_in_one_part() {
+        assert_eq!(split_message("  olá, mundo!  ", 2000), vec!["olá, mundo!"]);
+    }
+
+    #[test]
+    fn code_block_cut_in_half_is_reopened() {
+        let code = (0..300)
+            .map(|i| format!("let x{i} = {i};"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let content = format!("```rust\n{code}\n```");
+
+        let parts = split_message(&content, 2000);
+        assert!(parts.len() > 1);
+        for part in parts.iter() {
+            assert!(part.starts_with("```rust\n"));
+            assert!(part.ends_with("```"));
+            assert!(open_code_fence(part).is_none());
+        }
+    }
+
+    #[test]
+    fn every_part_fits_the_limit_after_closing_markdown() {
+        // An open fence and an open inline marker close to the limit
+        let contents = [
+            format!("```\n{}", "a".repeat(1996)),
+            format!("**{}", "palavra ".repeat(250)),
+            format!("texto ```py\n{}", "b ".repeat(3000)),
+            format!("__~~||`{}", "c".repeat(1993)),
+        ];
+
+        for content in contents {
+            for part in split_message(&content, DISCORD_MESSAGE_LIMIT) {
+                assert!(part.chars().count() <= DISCORD_MESSAGE_LIMIT);
+            }
+        }
+    }
+
+    #[test]
+    fn unbalanced_inline_markers_are_closed() {
+        assert_eq!(close_unbalanced_markdown("**negrito"), "**negrito**");
+        assert_eq!(close_unbalanced_markdown("**a** ~~b"), "**a** ~~b~~");
+        assert_eq!(
+            close_unbalanced_markdown("```\n**código"),
+            "```\n**código\n```"
+        );
+    }
+
+    #[test]
+    fn too_many_parts_fall_back_to_a_file() {
+        let content = "frase longa o bastante. ".repeat(300);
+
+        let rendered = render_message(&content, text_options(1));
+        assert_eq!(rendered.len(), 1);
+        let RenderedMessage::File {
+            preview,
+            content: file_content,
+            ..
+        } = &rendered[0]
+        else {
+            panic!("Expected a file");
+        };
+        assert_eq!(file_content, content.trim());
+        assert!(preview.chars().count() < DISCORD_MESSAGE_LIMIT);
+        assert_eq!(rendered[0].attachments().len(), 1);
+
+        let rendered = render_message(&content, text_options(10));
+        assert!(rendered.len() > 1);
+        assert!(
+            rendered
+                .iter()
+                .all(|message| matches!(message, RenderedMessage::Text(_)))
+        );
+    }
+
+    #[test]
+    fn italic_actions_are_stripped() {
+        assert_eq!(strip_italic_actions("*sorri* Oi! _acena_"), "Oi!");
+        assert_eq!(strip_italic_actions("Sem ações aqui."), "Sem ações aqui.");
+
+        let options = RenderOptions {
+            strip_italic_actions: true,
+            ..Default::default()
+        };
+        assert!(render_message("*sorri*", options).is_empty());
+    }
+
+    #[test]
+    fn italic_cut_in_half_is_closed() {
+        let content = format!("*{}*", "palavra ".repeat(400));
+
+        let parts = split_message(&content, DISCORD_MESSAGE_LIMIT);
+        assert!(parts.len() > 1);
+        for part in parts.iter() {
+            assert!(part.chars().count() <= DISCORD_MESSAGE_LIMIT);
+            assert_eq!(part.matches('*').count() % 2, 0);
+        }
+        assert!(parts[0].ends_with('*'));
+    }
+}
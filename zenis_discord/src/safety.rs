@@ -0,0 +1,213 @@
+use std::fmt::Display;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use twilight_model::{channel::message::AllowedMentions, id::Id};
+
+static MASS_MENTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"@(everyone|here)").unwrap());
+static ROLE_MENTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"<@&(\d+)>").unwrap());
+static USER_MENTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"<@!?(\d+)>").unwrap());
+static INVITE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(https?://)?(www\.)?(discord(app)?\.com/invite|discord\.gg|dsc\.gg)/[\w-]+")
+        .unwrap()
+});
+static LINK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)https?://([^\s/:?#<>()]+)[^\s<>()]*").unwrap());
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct OutputSafetyPolicy {
+    pub block_invites: bool,
+    /// Links outside `allowed_domains` (or their subdomains) are removed
+    pub block_links: bool,
+    pub allowed_domains: Vec<String>,
+    /// Users that can be pinged, usually the ones present in the conversation
+    pub mentionable_user_ids: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BlockedElement {
+    Invite(String),
+    Link(String),
+    MassMention(String),
+    RoleMention(u64),
+    UserMention(u64),
+}
+
+impl Display for BlockedElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invite(invite) => write!(f, "invite {invite}"),
+            Self::Link(link) => write!(f, "link {link}"),
+            Self::MassMention(mention) => write!(f, "mention {mention}"),
+            Self::RoleMention(id) => write!(f, "role mention {id}"),
+            Self::UserMention(id) => write!(f, "user mention {id}"),
+        }
+    }
+}
+
+impl OutputSafetyPolicy {
+    /// Nothing is pinged but the mentionable users, even if the message says @everyone
+    pub fn allowed_mentions(&self) -> AllowedMentions {
+        AllowedMentions {
+            parse: vec![],
+            replied_user: false,
+            roles: vec![],
+            users: self
+                .mentionable_user_ids
+                .iter()
+                .filter(|id| **id != 0)
+                .take(100)
+                .map(|id| Id::new(*id))
+                .collect(),
+        }
+    }
+
+    pub fn is_domain_allowed(&self, domain: &str) -> bool {
+        let domain = domain.to_lowercase();
+        let domain = domain.trim_start_matches("www.");
+        self.allowed_domains.iter().any(|allowed| {
+            let allowed = allowed.trim().to_lowercase();
+            let allowed = allowed.trim_start_matches("www.");
+            !allowed.is_empty() && (domain == allowed || domain.ends_with(&format!(".{allowed}")))
+        })
+    }
+
+    /// Returns the content with the blocked invites and links removed, and everything that was blocked.
+    /// Mentions stay in the text, since `allowed_mentions` already keeps them from pinging
+    pub fn sanitize(&self, content: &str) -> (String, Vec<BlockedElement>) {
+        let mut blocked = vec![];
+
+        for mention in MASS_MENTION.find_iter(content) {
+            blocked.push(BlockedElement::MassMention(mention.as_str().to_owned()));
+        }
+
+        for captures in ROLE_MENTION.captures_iter(content) {
+            if let Ok(id) = captures[1].parse() {
+                blocked.push(BlockedElement::RoleMention(id));
+            }
+        }
+
+        for captures in USER_MENTION.captures_iter(content) {
+            if let Ok(id) = captures[1].parse::<u64>()
+                && !self.mentionable_user_ids.contains(&id)
+            {
+                blocked.push(BlockedElement::UserMention(id));
+            }
+        }
+
+        let mut output = content.to_owned();
+        if self.block_invites {
+            output = INVITE
+                .replace_all(&output, |captures: &regex::Captures| {
+                    blocked.push(BlockedElement::Invite(captures[0].to_owned()));
+                    "[convite removido]"
+                })
+                .into_owned();
+        }
+
+        if self.block_links {
+            output = LINK
+                .replace_all(&output, |captures: &regex::Captures| {
+                    if self.is_domain_allowed(&captures[1]) {
+                        return captures[0].to_owned();
+                    }
+
+                    blocked.push(BlockedElement::Link(captures[0].to_owned()));
+                    "[link removido]".to_owned()
+                })
+                .into_owned();
+        }
+
+        (output, blocked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> OutputSafetyPolicy {
+        OutputSafetyPolicy {
+            block_invites: true,
+            block_links: true,
+            allowed_domains: vec!["youtube.com".to_string()],
+            mentionable_user_ids: vec![10, 20],
+        }
+    }
+
+    #[test]
+    fn mass_and_role_mentions_are_reported_and_never_pinged() {
+        let (output, blocked) = policy().sanitize("@everyone e @here, olhem <@&123>!");
+
+        assert_eq!(output, "@everyone e @here, olhem <@&123>!");
+        assert_eq!(
+            blocked,
+            vec![
+                BlockedElement::MassMention("@everyone".to_string()),
+                BlockedElement::MassMention("@here".to_string()),
+                BlockedElement::RoleMention(123),
+            ]
+        );
+
+        let allowed_mentions = policy().allowed_mentions();
+        assert!(allowed_mentions.parse.is_empty());
+        assert!(allowed_mentions.roles.is_empty());
+        assert!(!allowed_mentions.replied_user);
+    }
+
+    #[test]
+    fn only_conversation_users_can_be_mentioned() {
+        let (_, blocked) = policy().sanitize("oi <@10>, <@!20> e <@30>");
+        assert_eq!(blocked, vec![BlockedElement::UserMention(30)]);
+
+        let users = policy()
+            .allowed_mentions()
+            .users
+            .iter()
+            .map(|id| id.get())
+            .collect::<Vec<_>>();
+        assert_eq!(users, vec![10, 20]);
+    }
+
+    #[test]
+    fn invites_are_removed() {
+        let (output, blocked) =
+            policy().sanitize("entra aí: discord.gg/abc-1 ou https://discord.com/invite/xyz");
+
+        assert_eq!(output, "entra aí: [convite removido] ou [convite removido]");
+        assert_eq!(blocked.len(), 2);
+        assert!(
+            blocked
+                .iter()
+                .all(|element| matches!(element, BlockedElement::Invite(_)))
+        );
+    }
+
+    #[test]
+    fn links_outside_allowed_domains_are_removed() {
+        let (output, blocked) = policy().sanitize(
+            "veja https://www.youtube.com/watch?v=1 e https://m.youtube.com/x, não https://evil.com/youtube.com",
+        );
+
+        assert_eq!(
+            output,
+            "veja https://www.youtube.com/watch?v=1 e https://m.youtube.com/x, não [link removido]"
+        );
+        assert_eq!(
+            blocked,
+            vec![BlockedElement::Link(
+                "https://evil.com/youtube.com".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn nothing_is_removed_when_blocking_is_disabled() {
+        let policy = OutputSafetyPolicy::default();
+        let content = "discord.gg/abc https://evil.com";
+
+        let (output, blocked) = policy.sanitize(content);
+        assert_eq!(output, content);
+        assert!(blocked.is_empty());
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{CommandBuilder, CommandContext};
-use zenis_discord::twilight_model::id::{marker::ApplicationMarker, Id};
+use zenis_discord::twilight_model::{
+    application::command::CommandOptionChoice,
+    id::{marker::ApplicationMarker, Id},
+};
 
 #[derive(Debug, Clone)]
 pub struct CommandConfig;
@@ -9,4 +12,14 @@ pub trait Command {
     fn command_config(&self) -> CommandConfig;
     fn build_command(&self, application_id: Id<ApplicationMarker>) -> CommandBuilder;
     async fn run(&self, ctx: CommandContext) -> anyhow::Result<()>;
+
+    /// Suggestions for an option declared with `#[autocomplete(provider)]`, while the user types `query`
+    async fn autocomplete(
+        &self,
+        _ctx: CommandContext,
+        _option_name: &str,
+        _query: String,
+    ) -> anyhow::Result<Vec<CommandOptionChoice>> {
+        Ok(vec![])
+    }
 }
@@ -45,6 +45,21 @@ impl CommandBuilder {
         }
     }
 
+    /// Context-menu commands (message/user apps) must have an empty description and no options
+    pub fn new_context_menu(
+        application_id: Id<ApplicationMarker>,
+        name: impl Into<String>,
+        kind: CommandType,
+    ) -> Self {
+        let mut builder = Self::new(application_id, name, String::new());
+        builder.command.kind = kind;
+        builder
+    }
+
+    pub fn is_context_menu(&self) -> bool {
+        self.command.kind != CommandType::ChatInput
+    }
+
     pub fn set_guild_id(mut self, guild_id: Id<GuildMarker>) -> Self {
         self.command.guild_id = Some(guild_id);
         self
@@ -100,6 +115,11 @@ impl CommandOptionBuilder {
         self
     }
 
+    pub fn set_autocomplete(mut self, autocomplete: bool) -> Self {
+        self.option.autocomplete = Some(autocomplete);
+        self
+    }
+
     pub fn set_min_max_length(mut self, min: u16, max: u16) -> Self {
         self.option.min_length = Some(min);
         self.option.max_length = Some(max);
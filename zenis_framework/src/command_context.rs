@@ -3,13 +3,15 @@ use std::sync::Arc;
 use anyhow::Context;
 use zenis_database::ZenisDatabase;
 use zenis_discord::{
-    application_command::CommandDataOption,
+    application_command::{CommandData, CommandDataOption},
     twilight_http::{client::InteractionClient, Response as ApiResponse},
     twilight_model::{
+        application::command::CommandOptionChoice,
         channel::Message,
+        guild::Permissions,
         http::{
             attachment::Attachment,
-            interaction::{InteractionResponse, InteractionResponseType},
+            interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
         },
         id::{
             marker::{ChannelMarker, UserMarker},
@@ -82,6 +84,50 @@ impl CommandContext {
         self.interaction.author_id().unwrap()
     }
 
+    /// Role IDs of the author, when the command was used in a guild
+    pub fn author_role_ids(&self) -> Vec<u64> {
+        self.interaction
+            .member
+            .as_ref()
+            .map(|member| member.roles.iter().map(|id| id.get()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the author can manage the guild the command was used in
+    pub fn author_is_guild_admin(&self) -> bool {
+        self.interaction
+            .member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .is_some_and(|permissions| {
+                permissions.contains(Permissions::ADMINISTRATOR)
+                    || permissions.contains(Permissions::MANAGE_GUILD)
+            })
+    }
+
+    /// The message a message context-menu command was used on
+    pub fn target_message(&self) -> Option<Message> {
+        let data = self.command_data()?;
+        let target_id = data.target_id?;
+
+        data.resolved?.messages.remove(&target_id.cast())
+    }
+
+    /// The user a user context-menu command was used on
+    pub fn target_user(&self) -> Option<User> {
+        let data = self.command_data()?;
+        let target_id = data.target_id?;
+
+        data.resolved?.users.remove(&target_id.cast())
+    }
+
+    pub(crate) fn command_data(&self) -> Option<CommandData> {
+        match self.interaction.data.clone()? {
+            InteractionData::ApplicationCommand(data) => Some(*data),
+            _ => None,
+        }
+    }
+
     pub fn options(&self) -> OptionHandler {
         OptionHandler { ctx: self }
     }
@@ -130,6 +176,28 @@ impl CommandContext {
         Ok(())
     }
 
+    pub async fn respond_autocomplete(
+        &mut self,
+        choices: Vec<CommandOptionChoice>,
+    ) -> anyhow::Result<()> {
+        self.already_replied = true;
+
+        self.interaction_client()
+            .create_response(
+                self.interaction.id,
+                &self.interaction.token,
+                &InteractionResponse {
+                    kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+                    data: Some(InteractionResponseData {
+                        choices: Some(choices),
+                        ..Default::default()
+                    }),
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
     pub async fn reply(&mut self, response: impl Into<Response>) -> anyhow::Result<()> {
         if self.already_replied {
             self.followup_interaction(response).await?;
@@ -208,12 +276,18 @@ impl CommandContext {
         response: impl Into<Response>,
     ) -> anyhow::Result<ApiResponse<Message>> {
         let response = response.into();
-        let json = response.to_json();
+
+        const EMPTY_ARRAY: &[Attachment] = &[];
+        let attachments = match response.attachments.as_ref() {
+            Some(s) => s,
+            None => EMPTY_ARRAY,
+        };
 
         Ok(self
             .interaction_client()
             .create_followup(&self.interaction.token)
-            .payload_json(&json)
+            .attachments(attachments)
+            .payload_json(&response.clone().to_json())
             .await?)
     }
 
@@ -13,13 +13,22 @@ use zenis_discord::{
     ActionRowBuilder, ButtonBuilder, EmbedBuilder, Emoji, InteractionData, ModalBuilder,
 };
 
-use crate::{watcher::WatcherOptions, CommandContext, Response};
+use crate::{util::make_multiple_rows, watcher::WatcherOptions, CommandContext, Response};
+
+/// An extra button was clicked, which stops the pagination
+pub struct PaginationAction {
+    pub ctx: CommandContext,
+    pub custom_id: String,
+    pub page: usize,
+}
+
 pub struct EmbedPagination {
     ctx: CommandContext,
     pagination: Pagination<EmbedBuilder>,
     timeout: Duration,
     ephemeral: bool,
     allowed_users: Vec<Id<UserMarker>>,
+    extra_buttons: Vec<ButtonBuilder>,
 }
 
 impl EmbedPagination {
@@ -30,6 +39,7 @@ impl EmbedPagination {
             pagination: Pagination::new(pages),
             ephemeral: false,
             timeout: Duration::from_secs(300),
+            extra_buttons: vec![],
         }
     }
 
@@ -52,6 +62,11 @@ impl EmbedPagination {
         self
     }
 
+    pub fn add_extra_button(mut self, button: ButtonBuilder) -> Self {
+        self.extra_buttons.push(button);
+        self
+    }
+
     fn generate_embed(&self) -> EmbedBuilder {
         if self.pagination.pages.is_empty() {
             return self.pagination.get_current_page().clone();
@@ -68,7 +83,16 @@ impl EmbedPagination {
 
     fn generate_components(&self) -> Vec<Component> {
         if self.pagination.pages.len() < 2 {
-            return vec![];
+            if self.extra_buttons.is_empty() {
+                return vec![];
+            }
+
+            let mut row = ActionRowBuilder::new();
+            for button in &self.extra_buttons {
+                row = row.add_button(button.clone());
+            }
+
+            return vec![row.build()];
         }
 
         let previous = ButtonBuilder::new()
@@ -83,18 +107,17 @@ impl EmbedPagination {
             .set_custom_id("specific")
             .set_emoji(Emoji::from_unicode("🔍"));
 
+        let mut buttons = vec![previous, next];
         if self.pagination.pages.len() > 8 {
-            return vec![ActionRowBuilder::new()
-                .add_button(previous)
-                .add_button(next)
-                .add_button(specific)
-                .build()];
+            buttons.push(specific);
         }
 
-        vec![ActionRowBuilder::new()
-            .add_button(previous)
-            .add_button(next)
-            .build()]
+        // Extra buttons go to a second row once the first one is full
+        buttons.extend(self.extra_buttons.iter().cloned());
+        make_multiple_rows(buttons)
+            .into_iter()
+            .map(ActionRowBuilder::build)
+            .collect()
     }
 
     fn generate_response(&self) -> Response {
@@ -111,7 +134,8 @@ impl EmbedPagination {
         response
     }
 
-    pub async fn send(&mut self) -> anyhow::Result<()> {
+    /// Returns the extra button that stopped the pagination, if any
+    pub async fn send(&mut self) -> anyhow::Result<Option<PaginationAction>> {
         let allowed_users = self.allowed_users.clone();
         let message = self.ctx.send(self.generate_response()).await?;
 
@@ -135,6 +159,18 @@ impl EmbedPagination {
 
             let mut ctx = CommandContext::from_with_interaction(&self.ctx, Box::new(collected));
 
+            if self
+                .extra_buttons
+                .iter()
+                .any(|button| button.data.custom_id.as_ref() == Some(&data.custom_id))
+            {
+                return Ok(Some(PaginationAction {
+                    ctx,
+                    custom_id: data.custom_id,
+                    page: self.pagination.page,
+                }));
+            }
+
             if data.custom_id == "next" {
                 self.pagination.goto_next_page();
             } else if data.custom_id == "previous" {
@@ -189,6 +225,6 @@ impl EmbedPagination {
             ctx.update_message(self.generate_response()).await?;
         }
 
-        Ok(())
+        Ok(None)
     }
 }
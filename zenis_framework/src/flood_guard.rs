@@ -0,0 +1,261 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use zenis_common::config;
+use zenis_database::guild_model::FloodGuardSettings;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FloodVerdict {
+    /// The message enters the history normally
+    Accept,
+    /// Sent right after another message of the same user, so it's appended to it
+    Merge,
+    /// Over the limits, the message doesn't enter the history
+    Drop,
+}
+
+/// Why the guard started dropping messages. Only reported once per channel every `FLOOD_NOTICE_COOLDOWN_SECONDS`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FloodTrigger {
+    UserLimited(u64),
+    UserIgnored { user_id: u64, minutes: i64 },
+    ChannelLimited,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FloodCheck {
+    pub verdict: FloodVerdict,
+    pub trigger: Option<FloodTrigger>,
+}
+
+#[derive(Debug, Default)]
+struct FloodState {
+    /// Keyed by (channel, user)
+    user_messages: HashMap<(u64, u64), Vec<i64>>,
+    channel_messages: HashMap<u64, Vec<i64>>,
+    ignored_until: HashMap<(u64, u64), i64>,
+    last_notice: HashMap<u64, i64>,
+}
+
+/// Limits what enters the history of the agents in a channel. Timestamps are in milliseconds
+#[derive(Debug, Default)]
+pub struct FloodGuard {
+    state: Mutex<FloodState>,
+}
+
+impl FloodGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn check(
+        &self,
+        channel_id: u64,
+        user_id: u64,
+        settings: &FloodGuardSettings,
+        now: i64,
+    ) -> FloodCheck {
+        if !settings.enabled {
+            return FloodCheck {
+                verdict: FloodVerdict::Accept,
+                trigger: None,
+            };
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let window_start = now - config::FLOOD_WINDOW_SECONDS * 1000;
+        if state.user_messages.len() > 10_000 {
+            state.prune(window_start, now);
+        }
+
+        let user_key = (channel_id, user_id);
+        if let Some(until) = state.ignored_until.get(&user_key).copied() {
+            if until > now {
+                return FloodCheck {
+                    verdict: FloodVerdict::Drop,
+                    trigger: None,
+                };
+            }
+
+            state.ignored_until.remove(&user_key);
+        }
+
+        // Dropped messages still count for the user, so spamming keeps them limited
+        let user_messages = state.user_messages.entry(user_key).or_default();
+        user_messages.retain(|timestamp| *timestamp > window_start);
+        let last_user_message = user_messages.last().copied();
+        user_messages.push(now);
+        let user_count = user_messages.len();
+
+        let channel_messages = state.channel_messages.entry(channel_id).or_default();
+        channel_messages.retain(|timestamp| *timestamp > window_start);
+        let channel_count = channel_messages.len();
+
+        let trigger = if user_count > settings.max_messages_per_user as usize {
+            if settings.ignore_flooders {
+                let minutes = settings.ignore_minutes.max(1);
+                state
+                    .ignored_until
+                    .insert(user_key, now + minutes * 60 * 1000);
+                FloodTrigger::UserIgnored { user_id, minutes }
+            } else {
+                FloodTrigger::UserLimited(user_id)
+            }
+        } else if channel_count >= settings.max_messages_per_channel as usize {
+            FloodTrigger::ChannelLimited
+        } else {
+            state
+                .channel_messages
+                .entry(channel_id)
+                .or_default()
+                .push(now);
+            let is_rapid_fire = last_user_message
+                .is_some_and(|last| now - last <= config::FLOOD_MERGE_SECONDS * 1000);
+
+            return FloodCheck {
+                verdict: if is_rapid_fire {
+                    FloodVerdict::Merge
+                } else {
+                    FloodVerdict::Accept
+                },
+                trigger: None,
+            };
+        };
+
+        let notice_cooldown = config::FLOOD_NOTICE_COOLDOWN_SECONDS * 1000;
+        let should_notify = state
+            .last_notice
+            .get(&channel_id)
+            .is_none_or(|last| now - last > notice_cooldown)
+            // Ignoring someone is always worth telling
+            || matches!(trigger, FloodTrigger::UserIgnored { .. });
+        if should_notify {
+            state.last_notice.insert(channel_id, now);
+        }
+
+        FloodCheck {
+            verdict: FloodVerdict::Drop,
+            trigger: should_notify.then_some(trigger),
+        }
+    }
+}
+
+impl FloodState {
+    fn prune(&mut self, window_start: i64, now: i64) {
+        self.user_messages
+            .retain(|_, timestamps| timestamps.last().is_some_and(|last| *last > window_start));
+        self.channel_messages
+            .retain(|_, timestamps| timestamps.last().is_some_and(|last| *last > window_start));
+        self.ignored_until.retain(|_, until| *until > now);
+        self.last_notice
+            .retain(|_, last| now - *last <= config::FLOOD_NOTICE_COOLDOWN_SECONDS * 1000);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: i64 = 1000;
+
+    fn settings() -> FloodGuardSettings {
+        FloodGuardSettings {
+            enabled: true,
+            max_messages_per_user: 3,
+            max_messages_per_channel: 5,
+            ignore_flooders: false,
+            ignore_minutes: 5,
+        }
+    }
+
+    fn verdict(guard: &FloodGuard, channel_id: u64, user_id: u64, now: i64) -> FloodVerdict {
+        guard.check(channel_id, user_id, &settings(), now).verdict
+    }
+
+    #[test]
+    fn user_over_the_limit_is_dropped() {
+        let guard = FloodGuard::new();
+        for i in 0..3 {
+            assert_eq!(verdict(&guard, 1, 10, i * 5 * SECOND), FloodVerdict::Accept);
+        }
+
+        let check = guard.check(1, 10, &settings(), 15 * SECOND);
+        assert_eq!(check.verdict, FloodVerdict::Drop);
+        assert_eq!(check.trigger, Some(FloodTrigger::UserLimited(10)));
+
+        // The notice isn't repeated while on cooldown
+        let check = guard.check(1, 10, &settings(), 16 * SECOND);
+        assert_eq!(check.verdict, FloodVerdict::Drop);
+        assert_eq!(check.trigger, None);
+    }
+
+    #[test]
+    fn rapid_fire_messages_are_merged() {
+        let guard = FloodGuard::new();
+        assert_eq!(verdict(&guard, 1, 10, 0), FloodVerdict::Accept);
+        assert_eq!(verdict(&guard, 1, 10, 2 * SECOND), FloodVerdict::Merge);
+        assert_eq!(verdict(&guard, 1, 10, 10 * SECOND), FloodVerdict::Accept);
+    }
+
+    #[test]
+    fn users_and_channels_are_counted_apart() {
+        let guard = FloodGuard::new();
+        for i in 0..4 {
+            verdict(&guard, 1, 10, i * 5 * SECOND);
+        }
+        assert_eq!(verdict(&guard, 1, 10, 20 * SECOND), FloodVerdict::Drop);
+        assert_eq!(verdict(&guard, 1, 11, 20 * SECOND), FloodVerdict::Accept);
+        assert_eq!(verdict(&guard, 2, 10, 20 * SECOND), FloodVerdict::Accept);
+
+        // Many users under their own limit still fill the channel
+        for user_id in 20..25 {
+            assert_eq!(
+                verdict(&guard, 3, user_id, 20 * SECOND),
+                FloodVerdict::Accept
+            );
+        }
+        let check = guard.check(3, 25, &settings(), 20 * SECOND);
+        assert_eq!(check.verdict, FloodVerdict::Drop);
+        assert_eq!(check.trigger, Some(FloodTrigger::ChannelLimited));
+    }
+
+    #[test]
+    fn counters_reset_after_the_window() {
+        let guard = FloodGuard::new();
+        for i in 0..4 {
+            verdict(&guard, 1, 10, i * 5 * SECOND);
+        }
+        assert_eq!(verdict(&guard, 1, 10, 16 * SECOND), FloodVerdict::Drop);
+
+        let after_window = 16 * SECOND + config::FLOOD_WINDOW_SECONDS * SECOND + 1;
+        assert_eq!(verdict(&guard, 1, 10, after_window), FloodVerdict::Accept);
+    }
+
+    #[test]
+    fn flooders_are_ignored_for_a_while() {
+        let guard = FloodGuard::new();
+        let settings = FloodGuardSettings {
+            ignore_flooders: true,
+            ignore_minutes: 1,
+            ..settings()
+        };
+        for i in 0..3 {
+            guard.check(1, 10, &settings, i * 5 * SECOND);
+        }
+
+        let check = guard.check(1, 10, &settings, 15 * SECOND);
+        assert_eq!(
+            check.trigger,
+            Some(FloodTrigger::UserIgnored {
+                user_id: 10,
+                minutes: 1
+            })
+        );
+
+        // Past the flood window, but still ignored
+        let check = guard.check(1, 10, &settings, 50 * SECOND);
+        assert_eq!(check.verdict, FloodVerdict::Drop);
+
+        let check = guard.check(1, 10, &settings, 15 * SECOND + 60 * SECOND + 1);
+        assert_eq!(check.verdict, FloodVerdict::Accept);
+    }
+}
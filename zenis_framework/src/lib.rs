@@ -3,6 +3,7 @@ mod command_builder;
 mod command_context;
 mod context_helper;
 mod embed_pagination;
+mod flood_guard;
 mod framework;
 mod option_handler;
 mod response;
@@ -15,7 +16,8 @@ pub use command::*;
 pub use command_builder::{CommandBuilder, CommandOptionBuilder};
 pub use command_context::CommandContext;
 pub use context_helper::CommandContextHelper;
-pub use embed_pagination::EmbedPagination;
+pub use embed_pagination::{EmbedPagination, PaginationAction};
+pub use flood_guard::*;
 pub use framework::Framework;
 pub use option_handler::OptionHandler;
 pub use response::Response;
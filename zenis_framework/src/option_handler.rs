@@ -1,5 +1,6 @@
 use zenis_discord::twilight_model::{
-    application::interaction::application_command::CommandOptionValue, user::User,
+    application::interaction::application_command::CommandOptionValue, channel::Attachment,
+    user::User,
 };
 
 use crate::CommandContext;
@@ -79,4 +80,33 @@ impl<'a> OptionHandler<'a> {
             _ => None,
         })
     }
+
+    /// The option being typed in an autocomplete interaction, as (name, partial value)
+    pub fn get_focused(&self) -> Option<(String, String)> {
+        self.ctx
+            .options
+            .iter()
+            .find_map(|option| match &option.value {
+                CommandOptionValue::Focused(value, _) => Some((option.name.clone(), value.clone())),
+                _ => None,
+            })
+    }
+
+    pub fn get_attachment(
+        &self,
+        option_name: impl Into<String>,
+    ) -> anyhow::Result<Option<Attachment>> {
+        let Some(value) = self.get_option_value(option_name)? else {
+            return Ok(None);
+        };
+
+        Ok(match value {
+            CommandOptionValue::Attachment(attachment_id) => self
+                .ctx
+                .command_data()
+                .and_then(|data| data.resolved)
+                .and_then(|mut resolved| resolved.attachments.remove(&attachment_id)),
+            _ => None,
+        })
+    }
 }
@@ -1,3 +1,4 @@
+use zenis_database::instance_model::InstanceModel;
 use zenis_discord::*;
 
 pub fn make_multiple_rows(buttons: Vec<ButtonBuilder>) -> Vec<ActionRowBuilder> {
@@ -18,3 +19,29 @@ pub fn make_multiple_rows(buttons: Vec<ButtonBuilder>) -> Vec<ActionRowBuilder>
 
     rows
 }
+
+/// Whether Discord answered with 404, e.g. the webhook or channel doesn't exist anymore
+pub fn is_not_found_error(error: &twilight_http::Error) -> bool {
+    matches!(
+        error.kind(),
+        twilight_http::error::ErrorType::Response { status, .. } if status.get() == 404
+    )
+}
+
+/// Seconds Discord asked to wait before retrying, if the request was rate limited
+pub fn ratelimit_retry_after(error: &twilight_http::Error) -> Option<f64> {
+    match error.kind() {
+        twilight_http::error::ErrorType::Response {
+            error: twilight_http::api_error::ApiError::Ratelimited(ratelimited),
+            ..
+        } => Some(ratelimited.retry_after),
+        _ => None,
+    }
+}
+
+pub fn agent_render_options(instance: &InstanceModel) -> RenderOptions {
+    RenderOptions {
+        strip_italic_actions: instance.settings.strip_italic_actions,
+        ..Default::default()
+    }
+}
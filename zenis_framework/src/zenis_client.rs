@@ -1,23 +1,35 @@
 use anyhow::{bail, Context};
+use bson::oid::ObjectId;
 use std::{
+    collections::HashSet,
     fmt::Debug,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::Duration,
 };
-use zenis_ai::template::to_assistant_object;
-use zenis_common::{config, load_image_from_url, Color};
+use zenis_ai::{template::to_assistant_object, util::summarize_history};
+use zenis_common::{config, Color};
 use zenis_data::products::Product;
 use zenis_database::{
     agent_model::{AgentModel, AgentPricing},
-    instance_model::{CreditsPaymentMethod, InstanceBrain, InstanceMessage, InstanceModel},
+    archived_instance_model::ArchivedInstanceModel,
+    channel_webhook_model::ChannelWebhookModel,
+    guild_model::OutputSafetySettings,
+    instance_model::{
+        CreditsPaymentMethod, InstanceBrain, InstanceMessage, InstanceModel, InstanceSettings,
+    },
+    session_model::SessionModel,
     transaction::{CreditDestination, TransactionModel},
     user_model::AdminPermission,
     ZenisDatabase,
 };
 use zenis_discord::{
+    render_message,
+    twilight_http::{self, Response},
     twilight_model::{
+        channel::Message,
         guild::Guild,
         id::{
             marker::{ChannelMarker, GuildMarker, UserMarker},
@@ -25,14 +37,34 @@ use zenis_discord::{
         },
         user::{CurrentUser, User},
     },
-    DiscordHttpClient, EmbedAuthor, EmbedBuilder, GuildExtension,
+    DiscordHttpClient, EmbedAuthor, EmbedBuilder, GuildExtension, OutputSafetyPolicy,
+    RenderedMessage,
 };
 use zenis_payment::mp::{client::MercadoPagoClient, common::Item};
 
+use crate::{
+    util::{agent_render_options, is_not_found_error},
+    FloodGuard,
+};
+
+/// Everything needed to summon an agent in a channel
+#[derive(Debug, Clone)]
+pub struct AgentInstanceRequest {
+    pub brain: InstanceBrain,
+    pub channel_id: Id<ChannelMarker>,
+    pub summoner_id: Id<UserMarker>,
+    pub agent_model: AgentModel,
+    pub pricing: AgentPricing,
+    pub payment_method: CreditsPaymentMethod,
+    pub settings: InstanceSettings,
+    pub system_prompt: String,
+}
+
 #[derive(Debug)]
 pub struct ZenisClient {
     pub http: Arc<DiscordHttpClient>,
     pub is_ready: AtomicBool,
+    pub flood_guard: FloodGuard,
 
     pub mp_client: Arc<MercadoPagoClient>,
 }
@@ -42,6 +74,7 @@ impl ZenisClient {
         Self {
             http: Arc::new(DiscordHttpClient::new(token)),
             is_ready: AtomicBool::new(false),
+            flood_guard: FloodGuard::new(),
 
             mp_client: mp,
         }
@@ -94,48 +127,38 @@ impl ZenisClient {
     pub async fn create_agent_instance(
         &self,
         db: Arc<ZenisDatabase>,
-        brain: InstanceBrain,
-        (channel_id, summoner_id): (Id<ChannelMarker>, Id<UserMarker>),
-        agent_model: AgentModel,
-        pricing: AgentPricing,
-        payment_method: CreditsPaymentMethod,
-        mut system_prompt: String,
+        request: AgentInstanceRequest,
     ) -> anyhow::Result<()> {
+        let AgentInstanceRequest {
+            brain,
+            channel_id,
+            summoner_id,
+            agent_model,
+            pricing,
+            payment_method,
+            settings,
+            mut system_prompt,
+        } = request;
         let mut agent_model = db
             .agents()
             .get_by_identifier(&agent_model.identifier)
             .await?
             .context("Expected an agent with this identifier")?;
-        let image = match &agent_model.agent_url_image {
-            Some(url) => load_image_from_url(url).await.ok(),
-            None => None,
-        };
-
-        let webhook = self.http.create_webhook(channel_id, &agent_model.name);
-        let webhook = match image {
-            None => webhook.await?.model().await?,
-            Some(image) => webhook.avatar(&image.to_data_uri()).await?.model().await?,
-        };
+        let webhook = self.get_channel_webhook(&db, channel_id).await?;
 
-        system_prompt = system_prompt.replace("%WEBHOOK_ID%", &webhook.id.to_string());
-
-        let Some(token) = webhook.token.clone() else {
-            self.http.delete_webhook(webhook.id).await?;
-            bail!("Failed to create a webhook")
-        };
+        system_prompt = system_prompt.replace("%WEBHOOK_ID%", &webhook.webhook_id.to_string());
 
         let mut instance = InstanceModel::new(
             brain,
             (channel_id.get(), summoner_id.get()),
             agent_model.clone(),
             pricing,
-            (
-                webhook.id.get(),
-                webhook.token.context("Expected a HookToken")?,
-            ),
+            (webhook.webhook_id, webhook.webhook_token),
             payment_method,
             system_prompt,
-        );
+        )
+        .with_settings(settings);
+        instance.guild_id = webhook.guild_id;
 
         let introduction_message = instance.introduce(agent_model.introduction_message.clone());
         instance.already_introduced = true;
@@ -144,11 +167,18 @@ impl ZenisClient {
 
         if let Some(message) = assistant_object.message {
             if !assistant_object.is_noreply {
-                self.http
-                    .execute_webhook(webhook.id, &token)
-                    .content(&message)
-                    .await
-                    .ok();
+                let policy = self.output_safety_policy(&db, &instance).await?;
+                let (message, _) = policy.sanitize(&message);
+                for part in render_message(&message, agent_render_options(&instance)) {
+                    let Ok(response) = self.execute_agent_webhook(&instance, &part, &policy).await
+                    else {
+                        break;
+                    };
+
+                    if let Ok(sent_message) = response.model().await {
+                        instance.set_last_reply_message_id(sent_message.id.get());
+                    }
+                }
             }
         }
 
@@ -160,10 +190,354 @@ impl ZenisClient {
         Ok(())
     }
 
+    /// Returns the webhook shared by the agents of a channel, creating it on first use
+    async fn get_channel_webhook(
+        &self,
+        db: &ZenisDatabase,
+        channel_id: Id<ChannelMarker>,
+    ) -> anyhow::Result<ChannelWebhookModel> {
+        if let Some(webhook) = db
+            .channel_webhooks()
+            .get_by_channel(channel_id.get())
+            .await?
+        {
+            return Ok(webhook);
+        }
+
+        let webhook = self
+            .http
+            .create_webhook(channel_id, "Zenis")
+            .await?
+            .model()
+            .await?;
+
+        let Some(token) = webhook.token else {
+            self.http.delete_webhook(webhook.id).await?;
+            bail!("Failed to create a webhook")
+        };
+
+        let channel_webhook = ChannelWebhookModel::new(
+            channel_id.get(),
+            webhook.guild_id.map(Id::get),
+            (webhook.id.get(), token),
+        );
+
+        // Another invocation in the same channel may have created one first
+        if db
+            .channel_webhooks()
+            .create_webhook(channel_webhook.clone())
+            .await
+            .is_err()
+        {
+            self.http.delete_webhook(webhook.id).await.ok();
+            return db
+                .channel_webhooks()
+                .get_by_channel(channel_id.get())
+                .await?
+                .context("Expected a channel webhook");
+        }
+
+        Ok(channel_webhook)
+    }
+
+    /// What an agent may mention or link in its guild. Only users present in the conversation can be pinged
+    pub async fn output_safety_policy(
+        &self,
+        db: &ZenisDatabase,
+        instance: &InstanceModel,
+    ) -> anyhow::Result<OutputSafetyPolicy> {
+        let settings = match instance.guild_id {
+            Some(guild_id) => {
+                db.guilds()
+                    .get_by_guild(Id::new(guild_id))
+                    .await?
+                    .output_safety
+            }
+            None => OutputSafetySettings::default(),
+        };
+
+        Ok(OutputSafetyPolicy {
+            block_invites: settings.block_invites,
+            block_links: settings.block_links,
+            allowed_domains: settings.allowed_domains,
+            mentionable_user_ids: instance.participant_ids(),
+        })
+    }
+
+    /// Sends a message as the agent, with its name and avatar over the channel webhook
+    pub async fn execute_agent_webhook(
+        &self,
+        instance: &InstanceModel,
+        message: &RenderedMessage,
+        policy: &OutputSafetyPolicy,
+    ) -> Result<Response<Message>, twilight_http::Error> {
+        let attachments = message.attachments();
+        let allowed_mentions = policy.allowed_mentions();
+        let mut request = self
+            .http
+            .execute_webhook(Id::new(instance.webhook_id), &instance.webhook_token)
+            .username(&instance.agent_name)
+            .content(message.content())
+            .allowed_mentions(Some(&allowed_mentions));
+
+        if !attachments.is_empty() {
+            request = request.attachments(&attachments);
+        }
+
+        match &instance.agent_url_image {
+            Some(url) => request.avatar_url(url).wait().await,
+            None => request.wait().await,
+        }
+    }
+
+    /// Moves the instance to a new channel webhook after the old one was deleted by someone else
+    pub async fn recreate_instance_webhook(
+        &self,
+        db: &ZenisDatabase,
+        instance: &mut InstanceModel,
+    ) -> anyhow::Result<()> {
+        // Only the first agent of the channel to notice clears the stale webhook
+        if let Some(webhook) = db
+            .channel_webhooks()
+            .get_by_channel(instance.channel_id)
+            .await?
+        {
+            if webhook.webhook_id == instance.webhook_id {
+                db.channel_webhooks().delete_webhook(&webhook).await?;
+            }
+        }
+
+        let webhook = match self
+            .get_channel_webhook(db, Id::new(instance.channel_id))
+            .await
+        {
+            Ok(webhook) => webhook,
+            Err(e) => {
+                instance.exit_reason =
+                    Some("O webhook do agente foi apagado e não pôde ser recriado".to_string());
+                return Err(e);
+            }
+        };
+
+        instance.system_prompt = instance.system_prompt.replace(
+            &instance.webhook_id.to_string(),
+            &webhook.webhook_id.to_string(),
+        );
+        instance.webhook_id = webhook.webhook_id;
+        instance.webhook_token = webhook.webhook_token;
+        instance.guild_id = webhook.guild_id.or(instance.guild_id);
+
+        Ok(())
+    }
+
+    /// Instances summoned before they stored their guild get it from their channel, so the guild cleanup
+    /// and safety policy reach them. Agents whose channel no longer exists are shut down
+    async fn backfill_instance_guilds(&self, db: &ZenisDatabase) -> anyhow::Result<()> {
+        for mut instance in db.instances().all_actives().await? {
+            if instance.guild_id.is_some() {
+                continue;
+            }
+
+            match self.http.channel(Id::new(instance.channel_id)).await {
+                Ok(response) => {
+                    instance.guild_id = response.model().await?.guild_id.map(Id::get);
+                }
+                Err(e) if is_not_found_error(&e) => {
+                    instance.exit_reason = Some("O chat do agente não existe mais".to_string());
+                }
+                Err(_) => continue,
+            }
+
+            db.instances().save(instance).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes pooled webhooks of channels without agents, and bot webhooks no agent uses. Besides the
+    /// pooled channels, it looks at the channels of instances and archived conversations, where webhooks
+    /// of the old per-agent scheme or of failed deletions may have leaked
+    async fn reconcile_channel_webhooks(&self, db: &ZenisDatabase) -> anyhow::Result<()> {
+        let current_user = self.current_user().await?;
+
+        let pooled_webhooks = db.channel_webhooks().get_all().await?;
+        let mut channel_ids = pooled_webhooks
+            .iter()
+            .map(|webhook| webhook.channel_id)
+            .collect::<HashSet<_>>();
+        channel_ids.extend(
+            db.instances()
+                .all_actives()
+                .await?
+                .iter()
+                .map(|instance| instance.channel_id),
+        );
+        channel_ids.extend(db.archived_instances().get_all_channel_ids().await?);
+
+        for (index, channel_id) in channel_ids.into_iter().enumerate() {
+            if index > 0 && index % config::WEBHOOK_RECONCILE_BATCH_SIZE == 0 {
+                tokio::time::sleep(Duration::from_secs(
+                    config::WEBHOOK_RECONCILE_BATCH_INTERVAL_SECONDS,
+                ))
+                .await;
+            }
+
+            let instances = db.instances().get_all_by_channel(channel_id).await?;
+            let mut used_webhook_ids = instances
+                .iter()
+                .map(|instance| instance.webhook_id)
+                .collect::<Vec<_>>();
+
+            if let Some(webhook) = pooled_webhooks
+                .iter()
+                .find(|webhook| webhook.channel_id == channel_id)
+            {
+                if instances.is_empty() {
+                    db.channel_webhooks().delete_webhook(webhook).await?;
+                } else {
+                    used_webhook_ids.push(webhook.webhook_id);
+                }
+            }
+
+            let Ok(response) = self.http.channel_webhooks(Id::new(channel_id)).await else {
+                continue;
+            };
+
+            for channel_webhook in response.models().await? {
+                let is_own = channel_webhook
+                    .user
+                    .as_ref()
+                    .is_some_and(|user| user.id == current_user.id);
+
+                if is_own && !used_webhook_ids.contains(&channel_webhook.id.get()) {
+                    self.http.delete_webhook(channel_webhook.id).await.ok();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Re-summons the agent of a saved session, restoring its history and telling it what happened before
+    pub async fn resume_agent_instance(
+        &self,
+        db: Arc<ZenisDatabase>,
+        session: SessionModel,
+        (channel_id, summoner_id): (Id<ChannelMarker>, Id<UserMarker>),
+        pricing: AgentPricing,
+        payment_method: CreditsPaymentMethod,
+        mut system_prompt: String,
+    ) -> anyhow::Result<()> {
+        let agent_model = session.agent.to_agent_model();
+        let webhook = self.get_channel_webhook(&db, channel_id).await?;
+
+        system_prompt = system_prompt.replace("%WEBHOOK_ID%", &webhook.webhook_id.to_string());
+
+        let mut instance = InstanceModel::new(
+            session.brain,
+            (channel_id.get(), summoner_id.get()),
+            agent_model,
+            pricing,
+            (webhook.webhook_id, webhook.webhook_token),
+            payment_method,
+            system_prompt,
+        )
+        .with_settings(session.settings);
+
+        instance.guild_id = webhook.guild_id;
+        instance.history = session.history;
+        instance.session_id = Some(session.id);
+        instance.already_introduced = true;
+
+        let summary = if session.summary.is_empty() {
+            String::new()
+        } else {
+            format!(" Resumo do que aconteceu: {}", session.summary)
+        };
+        instance.push_message(InstanceMessage {
+            is_assistant: false,
+            user_id: 0,
+            text: format!(
+                "<!system_instruction/>Esta conversa é a continuação de uma sessão anterior.{summary} Cumprimente o chat e retome a conversa de onde parou."
+            ),
+            image_url: None,
+            message_id: None,
+        });
+        instance.is_awaiting_new_messages = false;
+
+        db.instances().create_instance(instance).await?;
+
+        if let Some(mut agent) = db
+            .agents()
+            .get_by_identifier(&session.agent.identifier)
+            .await?
+        {
+            agent.stats.invocations += 1;
+            db.agents().save(agent).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Saves the conversation of an instance right away, returning the session that still needs a summary
+    async fn save_instance_session(
+        &self,
+        db: &ZenisDatabase,
+        instance: &InstanceModel,
+        agent: &AgentModel,
+    ) -> anyhow::Result<ObjectId> {
+        let session = match instance.session_id {
+            Some(session_id) => db.sessions().get_by_id(session_id).await?,
+            None => None,
+        };
+
+        match session {
+            Some(mut session) => {
+                // The old summary stays until the new one is ready
+                let summary = session.summary.clone();
+                session.update_from_instance(instance, summary);
+                let session_id = session.id;
+                db.sessions().save(session).await?;
+                Ok(session_id)
+            }
+            None => {
+                let session = SessionModel::new(instance, agent, String::new());
+                let session_id = session.id;
+                db.sessions().create_session(session).await?;
+                db.sessions()
+                    .delete_oldest_over_limit(instance.summoner_id, config::MAX_SESSIONS_PER_USER)
+                    .await?;
+                Ok(session_id)
+            }
+        }
+    }
+
+    /// Summarizing is a slow LLM call, so it runs in the background instead of holding the cleanup loop
+    fn spawn_session_summary(
+        db: Arc<ZenisDatabase>,
+        session_id: ObjectId,
+        agent_name: String,
+        history: Vec<InstanceMessage>,
+    ) {
+        tokio::spawn(async move {
+            let Ok(summary) = summarize_history(&agent_name, &history, config::DEBUG).await else {
+                return;
+            };
+
+            db.sessions().set_summary(session_id, &summary).await.ok();
+        });
+    }
+
     pub async fn delete_off_instances(&self, db: Arc<ZenisDatabase>) -> anyhow::Result<()> {
         let instances = db.instances().all_inactives().await?;
 
         for instance in instances {
+            if !instance.history.is_empty() {
+                db.archived_instances()
+                    .create_archive(ArchivedInstanceModel::new(&instance))
+                    .await?;
+            }
+
             db.instances().delete_instance(instance.id).await?;
             let Some(agent) = db
                 .agents()
@@ -173,14 +547,35 @@ impl ZenisClient {
                 continue;
             };
 
+            if !instance.history.is_empty() {
+                if let Ok(session_id) = self.save_instance_session(&db, &instance, &agent).await {
+                    Self::spawn_session_summary(
+                        db.clone(),
+                        session_id,
+                        instance.agent_name.clone(),
+                        instance.history.clone(),
+                    );
+                }
+            }
+
             let exit_reason = instance
                 .exit_reason
                 .unwrap_or_else(|| "Razão não informada".to_string());
 
-            self.http
-                .delete_webhook(Id::new(instance.webhook_id))
-                .await
-                .ok();
+            // Instances from before webhook pooling had a webhook of their own
+            let pooled_webhook = db
+                .channel_webhooks()
+                .get_by_channel(instance.channel_id)
+                .await?;
+            let is_pooled = pooled_webhook
+                .as_ref()
+                .is_some_and(|webhook| webhook.webhook_id == instance.webhook_id);
+            if !is_pooled {
+                self.http
+                    .delete_webhook(Id::new(instance.webhook_id))
+                    .await
+                    .ok();
+            }
             let embeds = vec![EmbedBuilder::new_common()
                 .set_color(Color::RED)
                 .set_description(format!(
@@ -206,17 +601,45 @@ impl ZenisClient {
                     user_id: instance.webhook_id,
                     text: format!("<!agent_exit/>{}\n<!reason/>{}", agent.name, exit_reason),
                     image_url: None,
+                    message_id: None,
                 });
                 db.instances().save(channel_instance).await?;
             }
+
+            // The pooled webhook goes away with the last agent of the channel
+            if let Some(webhook) = pooled_webhook {
+                if db
+                    .instances()
+                    .get_all_by_channel(instance.channel_id)
+                    .await?
+                    .is_empty()
+                {
+                    db.channel_webhooks().delete_webhook(&webhook).await?;
+                    self.http
+                        .delete_webhook(Id::new(webhook.webhook_id))
+                        .await
+                        .ok();
+                }
+            }
         }
         Ok(())
     }
 
-    pub async fn init(&self, db: Arc<ZenisDatabase>) -> anyhow::Result<()> {
+    pub async fn init(self: Arc<Self>, db: Arc<ZenisDatabase>) -> anyhow::Result<()> {
         self.is_ready.swap(true, Ordering::Relaxed);
         let current_user = self.current_user().await?;
 
+        self.backfill_instance_guilds(&db).await.ok();
+
+        // It takes a Discord call per channel, so it runs in the background instead of delaying the startup
+        {
+            let client = self.clone();
+            let db = db.clone();
+            tokio::spawn(async move {
+                client.reconcile_channel_webhooks(&db).await.ok();
+            });
+        }
+
         let special_agents = db.agents().get_all_with_tags(&["special"]).await?;
         if special_agents.is_empty() {
             const TAGS: &[&str] = &["special"];
@@ -352,11 +775,15 @@ impl ZenisClient {
             .add_inlined_field(
                 "Informações",
                 format!(
-                    "{}**Nome**: `{}`\n**ID**: `{}`\n**Enviado por**: `{}`",
+                    "{}**Nome**: `{}`\n**ID**: `{}`\n**Enviado por**: `{}`{}",
                     if config::DEBUG { "(DEBUG) " } else { "" },
                     agent.name,
                     agent.identifier,
-                    agent.creator_user_id
+                    agent.creator_user_id,
+                    match &agent.forked_from {
+                        Some(lineage) => format!("\n**Baseado em**: `{}`", lineage.identifier),
+                        None => String::new(),
+                    }
                 ),
             )
             .add_inlined_field(
@@ -369,7 +796,7 @@ impl ZenisClient {
             )
             .add_not_inlined_field("📄 Descrição", format!("```{}```", agent.description))
             .add_footer_text(format!(
-                "ACEITAR: /adm cmd: accept {}\nRECUSAR: /adm cmd: reject {} <motivo>{}",
+                "FILA DE REVISÃO: /adm cmd: queue\nACEITAR: /adm cmd: accept {}\nRECUSAR: /adm cmd: reject {} <motivo>{}",
                 agent.identifier,
                 agent.identifier,
                 if config::DEBUG { " (DEBUG)" } else { "" }
@@ -1,7 +1,7 @@
-use syn::{spanned::Spanned, Attribute, Error, FnArg, Type};
+use syn::{spanned::Spanned, Attribute, Error, FnArg, Ident, Type};
 
 use crate::{
-    attr::{self, Attr},
+    attr::{self, Attr, Value},
     common::Result,
     util,
 };
@@ -13,6 +13,7 @@ pub struct Argument {
     pub ty: Type,
     pub description: String,
     pub min_max_length: Option<(u16, u16)>,
+    pub autocomplete: Option<Ident>,
 }
 
 impl Argument {
@@ -53,6 +54,15 @@ impl Argument {
             .flatten()
             .collect::<Vec<_>>();
 
+        let autocomplete = pat
+            .attrs
+            .iter()
+            .map(extract_attribute_autocomplete)
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
         if descriptions.len() > 1 {
             // We only want a single description attribute
             return Err(Error::new(
@@ -70,6 +80,7 @@ impl Argument {
             description: descriptions.remove(0),
             ty,
             min_max_length: min_max_length.first().cloned(),
+            autocomplete: autocomplete.first().cloned(),
         })
     }
 }
@@ -102,6 +113,23 @@ fn extract_attribute_description(attr: &Attribute) -> Result<Option<String>> {
     })
 }
 
+/// `#[autocomplete(provider)]`, where `provider` is an async fn taking the context and the typed query
+fn extract_attribute_autocomplete(attr: &Attribute) -> Result<Option<Ident>> {
+    exec(attr, |parsed| {
+        if parsed.path.is_ident("autocomplete") {
+            parsed.parse_value(|value| match value {
+                Value::Ident(ident) => Ok(Some(ident.clone())),
+                _ => Err(Error::new(
+                    value.span(),
+                    "Expected the name of an autocomplete function",
+                )),
+            })
+        } else {
+            Ok(None)
+        }
+    })
+}
+
 fn extract_attribute_min_max_length(attr: &Attribute) -> Result<Option<(u16, u16)>> {
     exec(attr, |parsed| {
         if parsed.path.is_ident("min_max_length") {
@@ -38,6 +38,29 @@ pub fn command(attr: TokenStream2, input: TokenStream2) -> Result<TokenStream2>
 
     let description = parse2::<syn::LitStr>(attr)?;
 
+    // Context-menu commands (`#[kind("message")]` / `#[kind("user")]`) have no options
+    let context_menu_kind = match util::get_attribute_argument_literal(&attrs, "kind") {
+        Some(Lit::Str(kind)) => match kind.value().as_str() {
+            "message" => Some(quote!(CommandType::Message)),
+            "user" => Some(quote!(CommandType::User)),
+            _ => {
+                return Err(Error::new(
+                    kind.span(),
+                    "Expected \"message\" or \"user\" as the command kind",
+                ))
+            }
+        },
+        Some(lit) => return Err(Error::new(lit.span(), "Command kind must be a string")),
+        None => None,
+    };
+
+    if context_menu_kind.is_some() && sig.inputs.len() > 1 {
+        return Err(Error::new(
+            sig.inputs.span(),
+            "Context-menu commands must only take CommandContext as a parameter",
+        ));
+    }
+
     let args = parse_arguments(&mut sig, &mut block)?;
 
     let option_tokens = {
@@ -61,14 +84,57 @@ pub fn command(attr: TokenStream2, input: TokenStream2) -> Result<TokenStream2>
                 ))
             }
 
+            if arg.autocomplete.is_some() {
+                builder_stream.extend(quote!(.set_autocomplete(true)))
+            }
+
             stream.extend(quote!(.add_option(#builder_stream)))
         }
 
         stream
     };
 
+    // Dispatches autocomplete interactions to the provider of the focused option
+    let autocomplete_tokens = {
+        let arms = args
+            .iter()
+            .filter_map(|arg| {
+                let provider = arg.autocomplete.as_ref()?;
+                let name = &arg.name;
+                Some(quote!(#name => #provider(&ctx, query).await,))
+            })
+            .collect::<Vec<_>>();
+
+        if arms.is_empty() {
+            quote!()
+        } else {
+            quote! {
+                async fn autocomplete(
+                    &self,
+                    ctx: CommandContext,
+                    option_name: &str,
+                    query: String,
+                ) -> anyhow::Result<Vec<CommandOptionChoice>> {
+                    match option_name {
+                        #(#arms)*
+                        _ => Ok(vec![]),
+                    }
+                }
+            }
+        }
+    };
+
     let struct_name = format_ident!("{}Command", capitalize(&name));
 
+    let builder_tokens = match context_menu_kind {
+        Some(kind) => {
+            quote!(CommandBuilder::new_context_menu(application_id, #command_name, #kind))
+        }
+        None => {
+            quote!(CommandBuilder::new(application_id, #command_name, #description) #option_tokens)
+        }
+    };
+
     // generate the code for the struct and impl
     let expanded = quote! {
         #vis struct #struct_name;
@@ -79,13 +145,14 @@ pub fn command(attr: TokenStream2, input: TokenStream2) -> Result<TokenStream2>
             }
 
             fn build_command(&self, application_id: Id<ApplicationMarker>) -> CommandBuilder {
-                CommandBuilder::new(application_id, #command_name, #description)
-                    #option_tokens
+                #builder_tokens
             }
 
             async fn run(&self, mut ctx: CommandContext) -> anyhow::Result<()> {
                 #block
             }
+
+            #autocomplete_tokens
         }
     };
 
@@ -124,6 +191,7 @@ pub fn parse_arguments(sig: &mut Signature, block: &mut Block) -> Result<Vec<Arg
             CommandOptionType::Integer => format_ident!("get_integer"),
             CommandOptionType::Number => format_ident!("get_number"),
             CommandOptionType::String => format_ident!("get_string"),
+            CommandOptionType::Attachment => format_ident!("get_attachment"),
             _ => unimplemented!(),
         };
 
@@ -102,6 +102,7 @@ impl From<Type> for OptionTypeWrapper {
             "f64" => Self(CommandOptionType::Number),
             "bool" => Self(CommandOptionType::Boolean),
             "User" => Self(CommandOptionType::User),
+            "Attachment" => Self(CommandOptionType::Attachment),
             _ => panic!("Unexpected type: {}", segment.ident),
         }
     }
@@ -115,6 +116,7 @@ impl ToTokens for OptionTypeWrapper {
             CommandOptionType::Number => format_ident!("Number"),
             CommandOptionType::Boolean => format_ident!("Boolean"),
             CommandOptionType::User => format_ident!("User"),
+            CommandOptionType::Attachment => format_ident!("Attachment"),
             _ => unimplemented!(),
         };
 